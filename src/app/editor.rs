@@ -0,0 +1,486 @@
+//! The level editor of `--edit`.
+//!
+//! The level goes up the screen the way the river comes down it, its first
+//! row at the bottom. The banks are painted row by row with the keyboard or
+//! the mouse, kinds of the content pack are placed and taken away at the
+//! cursor, and the rows the plane cannot get through are marked.
+
+use std::{mem, path::PathBuf};
+
+use crossterm::{
+    event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind},
+    style::{Attribute, ContentStyle, Stylize},
+};
+
+use crate::{
+    canvas::Canvas,
+    content::Content,
+    entities::Location,
+    level::{Level, LevelEnd},
+    world::store::Store,
+};
+
+/// Rows a turn of the mouse wheel scrolls
+const WHEEL: u32 = 3;
+
+const HELP: &str = "arrows move  [ ] banks  enter place  b across  x delete  tab kind  \
+    u undo  r redo  e end  t test  s save  q quit";
+
+/// What the editor needs the app for.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EditorRequest {
+    /// Play the level from this row
+    Playtest(u32),
+    Quit,
+}
+
+pub struct Editor {
+    path: PathBuf,
+    pub level: Level,
+    /// The kinds that can be placed, and the one placed next
+    kinds: Vec<String>,
+    kind: usize,
+    /// The levels before the last edits, and the ones undone
+    undo: Vec<Level>,
+    redo: Vec<Level>,
+    /// The level before the stroke of the mouse going on, until its first
+    /// edit makes it the one undone
+    stroke: Option<Level>,
+    /// A column of the screen and a row of the level
+    cursor: (u16, u32),
+    /// Row of the level on the bottom line of the view
+    bottom: u32,
+    /// Edited since the last save
+    dirty: bool,
+    /// Quit was pressed once with unsaved edits
+    quitting: bool,
+    /// Shown instead of the help, until the next key
+    message: Option<String>,
+    maxc: u16,
+    maxl: u16,
+}
+
+impl Editor {
+    pub fn new(path: PathBuf, level: Level, content: &Content, maxc: u16, maxl: u16) -> Editor {
+        let (left, right) = level.banks(0, maxc);
+        Editor {
+            path,
+            level,
+            kinds: content.placeable().map(String::from).collect(),
+            kind: 0,
+            undo: vec![],
+            redo: vec![],
+            stroke: None,
+            cursor: ((left + right) / 2, 0),
+            bottom: 0,
+            dirty: false,
+            quitting: false,
+            message: None,
+            maxc,
+            maxl,
+        }
+    }
+
+    /// Lines of the screen showing the level, the last two are for the status
+    fn view(&self) -> u16 {
+        self.maxl.saturating_sub(2).max(1)
+    }
+
+    fn row_of(&self, line: u16) -> u32 {
+        self.bottom + (self.view() - 1 - line) as u32
+    }
+
+    fn line_of(&self, row: u32) -> Option<u16> {
+        let above = row.checked_sub(self.bottom)?;
+        (above < self.view() as u32).then(|| self.view() - 1 - above as u16)
+    }
+
+    /// Scroll for the cursor to stay in view
+    fn follow(&mut self) {
+        let view = self.view() as u32;
+        let row = self.cursor.1;
+        self.bottom = self.bottom.min(row).max((row + 1).saturating_sub(view));
+    }
+
+    /// Edit the level, the way it was is kept for undo
+    fn change(&mut self, change: impl FnOnce(&mut Level) -> Result<(), String>) {
+        let before = self.level.clone();
+        match change(&mut self.level) {
+            Ok(()) => {
+                self.undo.push(before);
+                self.redo.clear();
+                self.dirty = true;
+            }
+            Err(err) => self.message = Some(err),
+        }
+    }
+
+    /// Edit the level within a stroke of the mouse, undone as one edit
+    fn paint(
+        &mut self,
+        change: impl FnOnce(&mut Level) -> Result<(), String>,
+    ) -> Result<(), String> {
+        change(&mut self.level)?;
+        if let Some(before) = self.stroke.take() {
+            self.undo.push(before);
+            self.redo.clear();
+        }
+        self.dirty = true;
+        Ok(())
+    }
+
+    fn undo(&mut self) {
+        match self.undo.pop() {
+            Some(level) => {
+                self.redo.push(mem::replace(&mut self.level, level));
+                self.dirty = true;
+            }
+            None => self.message = Some("nothing to undo".into()),
+        }
+    }
+
+    fn redo(&mut self) {
+        match self.redo.pop() {
+            Some(level) => {
+                self.undo.push(mem::replace(&mut self.level, level));
+                self.dirty = true;
+            }
+            None => self.message = Some("nothing to redo".into()),
+        }
+    }
+
+    fn save(&mut self) {
+        self.message = Some(match self.level.save(&self.path) {
+            Ok(()) => {
+                self.dirty = false;
+                format!("saved {}", self.path.display())
+            }
+            Err(err) => format!("cannot save {}: {err}", self.path.display()),
+        });
+    }
+
+    /// Put the selected kind at the cursor, or across its row
+    fn place(&mut self, span: bool) {
+        let ((column, row), maxc) = (self.cursor, self.maxc);
+        let kind = self.kinds[self.kind].clone();
+        self.change(|level| {
+            level.place(row, column, &kind, span, maxc);
+            Ok(())
+        });
+    }
+
+    pub fn key(&mut self, key: KeyEvent) -> Option<EditorRequest> {
+        let quitting = mem::take(&mut self.quitting);
+        self.message = None;
+        let ((column, row), maxc) = (self.cursor, self.maxc);
+        let view = self.view() as u32;
+
+        match key.code {
+            KeyCode::Left => self.cursor.0 = column.saturating_sub(1),
+            KeyCode::Right => self.cursor.0 = (column + 1).min(maxc - 1),
+            KeyCode::Up => self.cursor.1 = row + 1,
+            KeyCode::Down => self.cursor.1 = row.saturating_sub(1),
+            KeyCode::PageUp => self.cursor.1 = row + view,
+            KeyCode::PageDown => self.cursor.1 = row.saturating_sub(view),
+            KeyCode::Home => self.cursor.1 = 0,
+            KeyCode::End => self.cursor.1 = self.level.length() - 1,
+            KeyCode::Char('[') => self.change(|level| move_bank(level, row, column, true, maxc)),
+            KeyCode::Char(']') => self.change(|level| move_bank(level, row, column, false, maxc)),
+            KeyCode::Enter => self.place(false),
+            KeyCode::Char('b') => self.place(true),
+            KeyCode::Delete | KeyCode::Backspace | KeyCode::Char('x') => self.change(|level| {
+                if level.remove(row, column, maxc) {
+                    Ok(())
+                } else {
+                    Err("nothing to delete here".into())
+                }
+            }),
+            KeyCode::Tab => self.kind = (self.kind + 1) % self.kinds.len(),
+            KeyCode::BackTab => {
+                self.kind = self.kind.checked_sub(1).unwrap_or(self.kinds.len() - 1)
+            }
+            KeyCode::Char('u') => self.undo(),
+            KeyCode::Char('r') => self.redo(),
+            KeyCode::Char('e') => self.change(|level| {
+                level.end = match level.end {
+                    LevelEnd::Loop => LevelEnd::Procedural,
+                    LevelEnd::Procedural => LevelEnd::Loop,
+                };
+                Ok(())
+            }),
+            KeyCode::Char('t') => {
+                return Some(EditorRequest::Playtest(row.min(self.level.length() - 1)))
+            }
+            KeyCode::Char('s') => self.save(),
+            KeyCode::Esc | KeyCode::Char('q') => {
+                if !self.dirty || quitting {
+                    return Some(EditorRequest::Quit);
+                }
+                self.quitting = true;
+                self.message = Some("unsaved changes, q again to quit without saving".into());
+            }
+            _ => {}
+        }
+        self.follow();
+        None
+    }
+
+    /// The left button paints the nearer bank, the right one places or
+    /// deletes, the wheel scrolls.
+    pub fn mouse(&mut self, mouse: MouseEvent) {
+        let maxc = self.maxc;
+        if mouse.kind == MouseEventKind::Down(MouseButton::Left) {
+            // one undo for the whole stroke, whichever edit of it is the first
+            self.stroke = Some(self.level.clone());
+        }
+        match mouse.kind {
+            MouseEventKind::ScrollUp => {
+                self.bottom += WHEEL;
+                self.cursor.1 += WHEEL;
+                return;
+            }
+            MouseEventKind::ScrollDown => {
+                let rows = WHEEL.min(self.bottom);
+                self.bottom -= rows;
+                self.cursor.1 -= rows;
+                return;
+            }
+            _ if mouse.row >= self.view() || mouse.column >= maxc => return,
+            _ => {}
+        }
+
+        let (column, row) = (mouse.column, self.row_of(mouse.row));
+        self.cursor = (column, row);
+        let nearer = |level: &mut Level| {
+            let (left, right) = level.banks(row, maxc);
+            move_bank(level, row, column, column < (left + right) / 2, maxc)
+        };
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Err(err) = self.paint(nearer) {
+                    self.message = Some(err);
+                }
+            }
+            // the stroke goes on where the banks can go
+            MouseEventKind::Drag(MouseButton::Left) => {
+                self.paint(nearer).ok();
+            }
+            MouseEventKind::Down(MouseButton::Right) => {
+                let kind = self.kinds[self.kind].clone();
+                self.change(|level| {
+                    if !level.remove(row, column, maxc) {
+                        level.place(row, column, &kind, false, maxc);
+                    }
+                    Ok(())
+                });
+            }
+            _ => {}
+        }
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas, content: &Content) {
+        canvas.clear_all();
+        let theme = canvas.theme().clone();
+        let bank_fill = theme.bank_fill.to_string();
+        let length = self.level.length();
+        let impassable = self.level.impassable(content, self.maxc);
+        let mark = ContentStyle::new()
+            .with(theme.gauge_critical)
+            .on(theme.bank)
+            .attribute(Attribute::Bold);
+
+        for line in 0..self.view() {
+            let row = self.row_of(line);
+            if row == length {
+                let end = match self.level.end {
+                    LevelEnd::Loop => "back to the start",
+                    LevelEnd::Procedural => "the random river",
+                };
+                canvas.draw_styled_line((2, line), format!(" end, then {end} "), theme.hud_style());
+            }
+            if row >= length {
+                continue;
+            }
+
+            let (left, right) = self.level.banks(row, self.maxc);
+            canvas
+                .draw_styled_line(
+                    (0, line),
+                    bank_fill.repeat(left as usize),
+                    theme.bank_style(),
+                )
+                .draw_styled_line(
+                    (left, line),
+                    " ".repeat((right - left) as usize),
+                    theme.water_style(),
+                )
+                .draw_styled_line(
+                    (right, line),
+                    bank_fill.repeat((self.maxc - right) as usize),
+                    theme.bank_style(),
+                );
+            if impassable.contains(&row) {
+                canvas.draw_styled_line((0, line), "!", mark);
+            }
+        }
+
+        // the placed kinds, as they show up in the game
+        let mut store = Store::default();
+        for place in &self.level.places {
+            let (Some(line), Some(prototype)) = (self.line_of(place.at), content.kind(&place.kind))
+            else {
+                continue;
+            };
+            for column in self.level.columns(place, self.maxc) {
+                store.spawn(prototype, Location::new(column, line));
+            }
+        }
+        let mut entities: Vec<_> = (0..store.len()).collect();
+        entities.sort_by_key(|&entity| store.sprite[entity].layer);
+        for entity in entities {
+            canvas.draw(&store.get(entity));
+        }
+
+        let (column, row) = self.cursor;
+        if let Some(line) = self.line_of(row) {
+            let style = ContentStyle::new().attribute(Attribute::Reverse);
+            canvas.acquire_block(column as usize, line as usize, '+', style);
+        }
+
+        let passage = match impassable.first() {
+            Some(first) => format!("{} impassable rows, from {first}", impassable.len()),
+            None => "passable".into(),
+        };
+        let status = format!(
+            " {}{} | row {row} of {length} | {} | {passage} ",
+            self.path.display(),
+            if self.dirty { "*" } else { "" },
+            self.kinds[self.kind],
+        );
+        let help = self.message.as_deref().unwrap_or(HELP);
+        canvas
+            .draw_styled_line((0, self.view()), status, theme.hud_style())
+            .draw_line((0, self.view() + 1), format!(" {help}"));
+    }
+} // end of Editor implementation.
+
+/// Move the left or the right bank of `row` to `column`, the water starts
+/// or ends there.
+fn move_bank(
+    level: &mut Level,
+    row: u32,
+    column: u16,
+    left: bool,
+    maxc: u16,
+) -> Result<(), String> {
+    let banks = level.banks(row, maxc);
+    let banks = if left {
+        (column, banks.1)
+    } else {
+        (banks.0, column + 1)
+    };
+    level.set_banks(row, banks, maxc)
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyModifiers, MouseEvent};
+
+    use super::*;
+
+    fn editor() -> Editor {
+        let path =
+            std::env::temp_dir().join(format!("riverraid-level-{}.toml", std::process::id()));
+        Editor::new(path, Level::blank(), Content::built_in(), 40, 12)
+    }
+
+    fn keys(editor: &mut Editor, codes: &[KeyCode]) -> Option<EditorRequest> {
+        codes
+            .iter()
+            .find_map(|&code| editor.key(KeyEvent::from(code)))
+    }
+
+    fn click(editor: &mut Editor, kind: MouseEventKind, column: u16, line: u16) {
+        editor.mouse(MouseEvent {
+            kind,
+            column,
+            row: line,
+            modifiers: KeyModifiers::NONE,
+        });
+    }
+
+    #[test]
+    fn edits_undo_and_redo() {
+        let mut editor = editor();
+        assert_eq!(editor.cursor, (20, 0));
+        keys(
+            &mut editor,
+            &[
+                KeyCode::Up,
+                KeyCode::Left,
+                KeyCode::Char('['),
+                KeyCode::Enter,
+            ],
+        );
+        assert_eq!(editor.level.banks(1, 40), (19, 30));
+        assert_eq!(editor.level.places[0].at, 1);
+
+        keys(&mut editor, &[KeyCode::Char('u'), KeyCode::Char('u')]);
+        assert_eq!(editor.level, Level::blank());
+        keys(&mut editor, &[KeyCode::Char('r')]);
+        assert_eq!(editor.level.banks(1, 40), (19, 30));
+
+        // a stroke of the mouse is undone at once
+        click(&mut editor, MouseEventKind::Down(MouseButton::Left), 33, 9);
+        click(&mut editor, MouseEventKind::Drag(MouseButton::Left), 34, 8);
+        assert_eq!(editor.level.banks(0, 40), (19, 34));
+        assert_eq!(editor.level.banks(1, 40), (19, 35));
+        keys(&mut editor, &[KeyCode::Char('u')]);
+        assert_eq!(editor.level.banks(1, 40), (19, 30));
+    }
+
+    #[test]
+    fn a_stroke_is_undone_from_before_its_first_edit() {
+        let mut editor = editor();
+        editor.level.set_banks(0, (18, 21), 40).unwrap();
+        let before = editor.level.clone();
+
+        // too narrow where it starts, wider where it is dragged
+        click(&mut editor, MouseEventKind::Down(MouseButton::Left), 19, 9);
+        assert_eq!(editor.level, before);
+        click(&mut editor, MouseEventKind::Drag(MouseButton::Left), 24, 9);
+        assert_eq!(editor.level.banks(0, 40), (18, 25));
+
+        keys(&mut editor, &[KeyCode::Char('u')]);
+        assert_eq!(editor.level, before);
+        keys(&mut editor, &[KeyCode::Char('u')]);
+        assert_eq!(editor.message.as_deref(), Some("nothing to undo"));
+    }
+
+    #[test]
+    fn saves_and_asks_before_losing_edits() {
+        let mut editor = editor();
+        keys(&mut editor, &[KeyCode::Char('b')]);
+        assert_eq!(keys(&mut editor, &[KeyCode::Char('q')]), None);
+        assert_eq!(
+            keys(&mut editor, &[KeyCode::Char('q')]),
+            Some(EditorRequest::Quit)
+        );
+
+        keys(&mut editor, &[KeyCode::Char('s')]);
+        let saved = Level::load(&editor.path, Content::built_in()).unwrap();
+        std::fs::remove_file(&editor.path).unwrap();
+        assert_eq!(saved, editor.level);
+        assert!(saved.places[0].span);
+        assert_eq!(
+            keys(&mut editor, &[KeyCode::Char('q')]),
+            Some(EditorRequest::Quit)
+        );
+
+        keys(&mut editor, &[KeyCode::PageUp]);
+        assert_eq!(
+            keys(&mut editor, &[KeyCode::Char('t')]),
+            Some(EditorRequest::Playtest(10))
+        );
+    }
+}
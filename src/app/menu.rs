@@ -0,0 +1,85 @@
+use crossterm::style::{Attribute, ContentStyle, Stylize};
+
+use crate::canvas::Canvas;
+
+/// What happens when a menu item is picked.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MenuAction {
+    Play,
+    HighScores,
+    Options,
+    Credits,
+    MainMenu,
+    Quit,
+    NextGlyphs,
+    NextTheme,
+    NextSound,
+    NextMovement,
+    NextControls,
+    ToggleFuelBell,
+}
+
+#[derive(Default)]
+pub struct Menu {
+    items: Vec<(String, MenuAction)>,
+    selected: usize,
+    /// Where it was last drawn, for the clicks
+    at: (u16, u16),
+}
+
+impl Menu {
+    pub fn new(items: Vec<(String, MenuAction)>) -> Menu {
+        Menu {
+            items,
+            selected: 0,
+            at: (0, 0),
+        }
+    }
+
+    /// Change the labels, keeping the selection.
+    pub fn relabel(&mut self, items: Vec<(String, MenuAction)>) {
+        self.items = items;
+        self.selected = self.selected.min(self.items.len().saturating_sub(1));
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.checked_sub(1).unwrap_or(self.items.len() - 1);
+    }
+
+    pub fn down(&mut self) {
+        self.selected = (self.selected + 1) % self.items.len();
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.selected = index.min(self.items.len().saturating_sub(1));
+    }
+
+    /// The item drawn over the cell, if any
+    pub fn item_at(&self, c: u16, l: u16) -> Option<usize> {
+        let index = l.checked_sub(self.at.1)? as usize;
+        let (label, _) = self.items.get(index)?;
+        let width = label.chars().count() as u16 + 3;
+        (c >= self.at.0 && c < self.at.0 + width).then_some(index)
+    }
+
+    pub fn selected(&self) -> Option<MenuAction> {
+        self.items.get(self.selected).map(|(_, action)| *action)
+    }
+
+    pub fn height(&self) -> u16 {
+        self.items.len() as u16
+    }
+
+    pub fn draw(&mut self, canvas: &mut Canvas, c: u16, l: u16) {
+        self.at = (c, l);
+        for (index, (label, _)) in self.items.iter().enumerate() {
+            let line = l + index as u16;
+            if index == self.selected {
+                let style = ContentStyle::new().attribute(Attribute::Reverse);
+                canvas.draw_styled_line((c, line), format!("> {label} "), style);
+            } else {
+                canvas.draw_line((c, line), format!("  {label} "));
+            }
+        }
+    }
+} // end of Menu implementation.
@@ -0,0 +1,432 @@
+//! The screens of the game and how to go from one to another.
+//!
+//! Every screen is drawn on the same [`Canvas`] by the same loop, only the
+//! changed cells reach the terminal. With `--edit` the app is the level
+//! editor, its games are playtests.
+//!
+//! The screens keep the size of the terminal at the start. Resized smaller
+//! than that, the game pauses and the terminal says it is too small, until
+//! it grows back.
+
+use std::{
+    io::Write,
+    time::{Duration, Instant},
+};
+
+use crossterm::{
+    event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind},
+    terminal::size,
+};
+
+use crate::{
+    canvas::Canvas,
+    events::handle_actions,
+    high_scores::HighScores,
+    input::{Frame, Input},
+    level::Level,
+    options::Options,
+    stout_ext::StdoutExt,
+    terminal::{Signal, Terminal},
+    world::{World, WorldStatus},
+};
+
+mod editor;
+mod menu;
+mod screens;
+
+use editor::{Editor, EditorRequest};
+use menu::{Menu, MenuAction};
+
+/// How long a frame lasts
+const SLOWNESS: Duration = Duration::from_millis(60);
+
+/// Frames the game over screen ignores the keyboard, so the keys still
+/// hammered at the crash don't skip it.
+const GAME_OVER_GUARD: u32 = 25;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Screen {
+    /// The title, until a key is pressed
+    Title,
+    MainMenu,
+    /// Playing, or paused, see [`WorldStatus`](crate::world::WorldStatus)
+    Game,
+    GameOver,
+    HighScores,
+    Options,
+    Credits,
+    /// The level editor of `--edit`
+    Editor,
+}
+
+pub struct App {
+    canvas: Canvas,
+    options: Options,
+    world: World,
+    high_scores: HighScores,
+    screen: Screen,
+    menu: Menu,
+    input: Input,
+    /// The level editor, when editing
+    editor: Option<Editor>,
+    /// Rank of the last game in the high scores, if it made it
+    last_rank: Option<usize>,
+    /// Frames since the start, drives the animations
+    frame: u32,
+    /// The frame the current screen was entered at
+    screen_since: u32,
+    maxc: u16,
+    maxl: u16,
+    /// The size of the terminal, while it is smaller than the screens
+    too_small: Option<(u16, u16)>,
+    running: bool,
+}
+
+impl App {
+    pub fn new(maxc: u16, maxl: u16, options: Options, high_scores: HighScores) -> App {
+        let editor = options.edit.clone().map(|path| {
+            let level = options.level.clone().unwrap_or_else(Level::blank);
+            Editor::new(path, level, &options.content, maxc, maxl)
+        });
+        let screen = match editor {
+            Some(_) => Screen::Editor,
+            None => Screen::Title,
+        };
+        let mut app = App {
+            canvas: Canvas::new(maxc, maxl),
+            world: World::new(maxc, maxl),
+            options,
+            high_scores,
+            screen: Screen::Title,
+            menu: Menu::default(),
+            input: Input::default(),
+            editor,
+            last_rank: None,
+            frame: 0,
+            screen_since: 0,
+            maxc,
+            maxl,
+            too_small: None,
+            running: true,
+        };
+        app.apply_options();
+        app.switch_to(screen);
+        app
+    }
+
+    pub fn with_input(mut self, input: Input) -> App {
+        self.input = input;
+        self
+    }
+
+    pub fn run(&mut self, terminal: &mut Terminal) -> std::io::Result<()> {
+        let mut deadline = Instant::now();
+        while self.running {
+            match terminal.signal() {
+                Some(Signal::Quit) => break,
+                Some(Signal::Suspend) => self.suspend(terminal)?,
+                Some(Signal::Continue) => {
+                    terminal.resume()?;
+                    self.canvas.force_redraw(terminal.stdout())?;
+                }
+                None => {}
+            }
+
+            // a slow terminal should not make the next frames rush
+            deadline = (deadline + SLOWNESS).max(Instant::now());
+            let frame = self.input.next_frame(deadline)?;
+            if frame.interrupt {
+                terminal.interrupt();
+                continue;
+            }
+            if frame.suspend {
+                self.suspend(terminal)?;
+            }
+            if frame.redraw {
+                self.fit(size()?);
+                terminal.stdout().clear_all()?;
+                if let Some((columns, lines)) = self.too_small {
+                    let message = format!(
+                        "Terminal too small: {columns}x{lines}, the game needs {}x{}",
+                        self.maxc, self.maxl
+                    );
+                    let message: String = message.chars().take(columns as usize).collect();
+                    terminal.stdout().draw((0, 0), message)?.flush()?;
+                }
+            }
+            if self.too_small.is_some() {
+                // nothing goes on until the screens fit again
+                continue;
+            }
+            self.handle_input(&frame);
+            self.update();
+            self.draw();
+
+            self.canvas.draw_map(terminal.stdout())?;
+        }
+
+        Ok(())
+    }
+
+    /// Stop until continued by the shell, the game waits paused
+    fn suspend(&mut self, terminal: &mut Terminal) -> std::io::Result<()> {
+        self.pause();
+        terminal.suspend()?;
+        self.canvas.force_redraw(terminal.stdout())
+    }
+
+    /// Follow the terminal resized to `columns` and `lines`, the game pauses
+    /// while the screens do not fit.
+    fn fit(&mut self, (columns, lines): (u16, u16)) {
+        self.too_small = (columns < self.maxc || lines < self.maxl).then_some((columns, lines));
+        if self.too_small.is_some() {
+            self.pause();
+        }
+    }
+
+    /// Time for the periodic full redraw of `--redraw-every`
+    fn healing_due(&self) -> bool {
+        self.options.redraw_every.is_some_and(|every| {
+            let frames = (every.as_secs_f32() / SLOWNESS.as_secs_f32()).max(1.0) as u32;
+            self.frame.is_multiple_of(frames)
+        })
+    }
+
+    fn pause(&mut self) {
+        if self.screen == Screen::Game {
+            self.world.status = WorldStatus::Paused;
+        }
+    }
+
+    fn apply_options(&mut self) {
+        self.canvas.set_theme(self.options.theme.clone());
+        self.canvas.set_glyphs(self.options.glyphs);
+    }
+
+    /// A new game, on the level played or edited from its row `from`
+    fn new_world(&self, from: u32) -> World {
+        let world = World::new(self.maxc, self.maxl)
+            .with_theme(self.options.theme.clone())
+            .with_glyphs(self.options.glyphs)
+            .with_sound(self.options.sound.bus())
+            .with_movement(self.options.movement)
+            .with_controls(self.options.controls)
+            .with_fuel_warning(self.options.fuel_warning)
+            .with_content(&self.options.content);
+        let level = match &self.editor {
+            Some(editor) => Some(&editor.level),
+            None => self.options.level.as_ref(),
+        };
+        match level {
+            Some(level) => world.with_level(level, &self.options.content, from),
+            None => world,
+        }
+    }
+
+    fn switch_to(&mut self, screen: Screen) {
+        self.menu = match screen {
+            Screen::MainMenu => Menu::new(vec![
+                ("Play".into(), MenuAction::Play),
+                ("High scores".into(), MenuAction::HighScores),
+                ("Options".into(), MenuAction::Options),
+                ("Credits".into(), MenuAction::Credits),
+                ("Quit".into(), MenuAction::Quit),
+            ]),
+            Screen::Title | Screen::Game | Screen::Editor => Menu::default(),
+            Screen::GameOver => Menu::new(vec![
+                ("Play again".into(), MenuAction::Play),
+                ("Main menu".into(), MenuAction::MainMenu),
+                ("Quit".into(), MenuAction::Quit),
+            ]),
+            Screen::HighScores | Screen::Credits => {
+                Menu::new(vec![("Back".into(), MenuAction::MainMenu)])
+            }
+            Screen::Options => Menu::new(self.option_items()),
+        };
+        self.screen = screen;
+        self.screen_since = self.frame;
+    }
+
+    /// Frames spent on the current screen
+    fn screen_age(&self) -> u32 {
+        self.frame - self.screen_since
+    }
+
+    fn option_items(&self) -> Vec<(String, MenuAction)> {
+        vec![
+            (
+                format!("Glyphs: {}", self.options.glyphs.name),
+                MenuAction::NextGlyphs,
+            ),
+            (
+                format!("Theme: {}", self.options.theme.name),
+                MenuAction::NextTheme,
+            ),
+            (
+                format!("Sound: {}", self.options.sound.name()),
+                MenuAction::NextSound,
+            ),
+            (
+                format!("Movement: {}", self.options.movement.name()),
+                MenuAction::NextMovement,
+            ),
+            (
+                format!("Controls: {}", self.options.controls.name()),
+                MenuAction::NextControls,
+            ),
+            (
+                format!("Fuel bell: {}", on_off(self.options.fuel_warning.bell)),
+                MenuAction::ToggleFuelBell,
+            ),
+            ("Back".into(), MenuAction::MainMenu),
+        ]
+    }
+
+    fn handle_input(&mut self, frame: &Frame) {
+        if frame.redraw || self.healing_due() {
+            self.canvas.invalidate();
+        }
+        if frame.focus_lost {
+            self.pause();
+        }
+        if self.screen == Screen::Editor {
+            self.edit(frame);
+            return;
+        }
+        for &key in &frame.keys {
+            if self.screen == Screen::Game {
+                break;
+            }
+            self.handle_key(key);
+        }
+        for &mouse in &frame.mouse {
+            if self.screen == Screen::Game {
+                break;
+            }
+            self.handle_mouse(mouse);
+        }
+
+        if self.screen == Screen::Game {
+            handle_actions(&mut self.world, frame);
+        }
+    }
+
+    fn edit(&mut self, frame: &Frame) {
+        let Some(editor) = &mut self.editor else {
+            return;
+        };
+        for &mouse in &frame.mouse {
+            editor.mouse(mouse);
+        }
+        match frame.keys.iter().find_map(|&key| editor.key(key)) {
+            Some(EditorRequest::Playtest(row)) => {
+                self.world = self.new_world(row);
+                self.switch_to(Screen::Game);
+            }
+            Some(EditorRequest::Quit) => self.running = false,
+            None => {}
+        }
+    }
+
+    /// A key on one of the menus
+    fn handle_key(&mut self, key: KeyEvent) {
+        if self.screen == Screen::Title {
+            self.switch_to(Screen::MainMenu);
+            return;
+        }
+        if self.screen == Screen::GameOver && self.screen_age() < GAME_OVER_GUARD {
+            return;
+        }
+
+        match key.code {
+            KeyCode::Up | KeyCode::Char('w') => self.menu.up(),
+            KeyCode::Down | KeyCode::Char('s') => self.menu.down(),
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if let Some(action) = self.menu.selected() {
+                    self.perform(action);
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => match self.screen {
+                Screen::MainMenu => self.running = false,
+                _ => self.switch_to(Screen::MainMenu),
+            },
+            _ => {}
+        }
+    }
+
+    /// Pointing at a menu item selects it, clicking picks it
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        let click = mouse.kind == MouseEventKind::Down(MouseButton::Left);
+        if self.screen == Screen::Title {
+            if click {
+                self.switch_to(Screen::MainMenu);
+            }
+            return;
+        }
+        if self.screen == Screen::GameOver && self.screen_age() < GAME_OVER_GUARD {
+            return;
+        }
+
+        let Some(index) = self.menu.item_at(mouse.column, mouse.row) else {
+            return;
+        };
+        if mouse.kind == MouseEventKind::Moved || click {
+            self.menu.select(index);
+        }
+        if let (true, Some(action)) = (click, self.menu.selected()) {
+            self.perform(action);
+        }
+    }
+
+    fn perform(&mut self, action: MenuAction) {
+        match action {
+            MenuAction::Play => {
+                self.world = self.new_world(0);
+                self.switch_to(Screen::Game);
+            }
+            MenuAction::HighScores => self.switch_to(Screen::HighScores),
+            MenuAction::Options => self.switch_to(Screen::Options),
+            MenuAction::Credits => self.switch_to(Screen::Credits),
+            MenuAction::MainMenu => self.switch_to(Screen::MainMenu),
+            MenuAction::Quit => self.running = false,
+            MenuAction::NextGlyphs => self.change_options(Options::next_glyphs),
+            MenuAction::NextTheme => self.change_options(Options::next_theme),
+            MenuAction::NextSound => self.change_options(Options::next_sound),
+            MenuAction::NextMovement => self.change_options(Options::next_movement),
+            MenuAction::NextControls => self.change_options(Options::next_controls),
+            MenuAction::ToggleFuelBell => self.change_options(Options::toggle_fuel_bell),
+        }
+    }
+
+    fn change_options(&mut self, change: fn(&mut Options)) {
+        change(&mut self.options);
+        self.apply_options();
+        self.menu.relabel(self.option_items());
+    }
+
+    fn update(&mut self) {
+        self.frame += 1;
+        if self.screen != Screen::Game {
+            return;
+        }
+
+        self.world.tick();
+        if self.world.is_over() && self.editor.is_some() {
+            // back to editing, playtests make no high scores
+            self.switch_to(Screen::Editor);
+        } else if self.world.is_over() {
+            self.last_rank = self.high_scores.insert(self.world.player.score);
+            // the scores are a nicety, a read-only home is not worth a crash
+            let _ = self.high_scores.save();
+            self.switch_to(Screen::GameOver);
+        }
+    }
+} // end of App implementation.
+
+fn on_off(on: bool) -> &'static str {
+    if on {
+        "on"
+    } else {
+        "off"
+    }
+}
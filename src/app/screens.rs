@@ -0,0 +1,383 @@
+use crate::{
+    app::{App, Screen, GAME_OVER_GUARD},
+    entities::{DeathCause, PlayerStatus},
+    world::WorldStatus,
+};
+
+const CREDITS: &str = "River Raid Rust
+A rewrite of the classic game river raid, in Rust.
+
+Written by Jadi and the contributors,
+live on screen casts, for fun and education.
+
+https://github.com/jadijadi/riverraidrust";
+
+/// Scrolls under the title.
+const ABOUT: &str = "A rewrite of the classic game river raid, in Rust. \
+    Stay in the river, shoot the enemies and don't run out of fuel. \
+    Written by Jadi and the contributors, live on screen casts, for fun and education.";
+
+/// Frames the "Press any key" prompt stays on, then off.
+const BLINK: u32 = 8;
+
+impl App {
+    pub(super) fn draw(&mut self) {
+        if self.screen == Screen::Game {
+            self.world.draw_on_canvas(&mut self.canvas);
+            if let WorldStatus::Paused = self.world.status {
+                self.world.pause_screen(&mut self.canvas);
+            }
+            return;
+        }
+        if let (Screen::Editor, Some(editor)) = (self.screen, &self.editor) {
+            editor.draw(&mut self.canvas, &self.options.content);
+            return;
+        }
+
+        self.canvas.clear_all();
+        let mut line = match self.screen {
+            Screen::Title => self.title_screen(),
+            Screen::MainMenu => self.main_menu_screen(),
+            Screen::GameOver => self.game_over_screen(),
+            Screen::HighScores => self.high_scores_screen(),
+            Screen::Options => self.options_screen(),
+            Screen::Credits => self.credits_screen(),
+            Screen::Game | Screen::Editor => unreachable!(),
+        };
+
+        if self.screen == Screen::GameOver && self.screen_age() < GAME_OVER_GUARD {
+            return;
+        }
+        line = (line + 1).min(self.maxl.saturating_sub(self.menu.height()));
+        self.menu.draw(&mut self.canvas, 2, line);
+    }
+
+    /// Draw text from `line` on, returns the line after it
+    fn text(&mut self, line: u16, text: &str) -> u16 {
+        self.canvas.draw_text((0, line), text, None);
+        line + text.lines().count() as u16
+    }
+
+    fn title_screen(&mut self) -> u16 {
+        let title = self.canvas.glyphs().title.fit(self.maxc);
+        let line = self.text(2, title) + 1;
+
+        let about = marquee(ABOUT, self.maxc as usize, self.frame as usize);
+        self.canvas.draw_line((0, line), about);
+
+        if (self.frame / BLINK).is_multiple_of(2) {
+            let prompt = "Press any key";
+            let c = self.maxc.saturating_sub(prompt.len() as u16) / 2;
+            let l = (line + 2).max(self.maxl.saturating_sub(3));
+            self.canvas.draw_line((c, l), prompt);
+        }
+        line + 1
+    }
+
+    fn main_menu_screen(&mut self) -> u16 {
+        let title = self.canvas.glyphs().title.fit(self.maxc);
+        self.text(2, title)
+    }
+
+    fn game_over_screen(&mut self) -> u16 {
+        let glyphs = self.canvas.glyphs();
+        let mut line = self.text(2, glyphs.good_game.fit(self.maxc)) + 1;
+
+        let thanks = glyphs.thanks.fit(self.maxc);
+        if line + thanks.lines().count() as u16 + 10 < self.maxl {
+            line = self.text(line, thanks) + 1;
+        }
+
+        if let PlayerStatus::Dead(cause) = &self.world.player.status {
+            let banner = match cause {
+                DeathCause::Ground => &glyphs.crashed,
+                DeathCause::Enemy => &glyphs.killed,
+                DeathCause::Fuel => &glyphs.no_fuel,
+            };
+            line = self.text(line, banner.fit(self.maxc)) + 1;
+        }
+
+        let score = match self.last_rank {
+            Some(0) => format!("Score: {}, a new high score!", self.world.player.score),
+            Some(rank) => format!(
+                "Score: {}, #{} in the high scores",
+                self.world.player.score,
+                rank + 1
+            ),
+            None => format!("Score: {}", self.world.player.score),
+        };
+        self.text(line, &format!("  {score}"))
+    }
+
+    fn high_scores_screen(&mut self) -> u16 {
+        let mut line = self.text(2, "  High scores") + 1;
+        if self.high_scores.scores().is_empty() {
+            return self.text(line, "  No games played yet.");
+        }
+
+        let scores: Vec<String> = (self.high_scores.scores().iter().enumerate())
+            .map(|(rank, score)| format!("  {:>2}. {score:>6}", rank + 1))
+            .collect();
+        for score in scores {
+            line = self.text(line, &score);
+        }
+        line
+    }
+
+    fn options_screen(&mut self) -> u16 {
+        self.text(2, "  Options")
+    }
+
+    fn credits_screen(&mut self) -> u16 {
+        let mut line = 2;
+        for credit in CREDITS.lines() {
+            line = self.text(line, &format!("  {credit}"));
+        }
+        line
+    }
+}
+
+/// A `width` wide window on `text` going around in circles, `offset`
+/// characters from its start.
+fn marquee(text: &str, width: usize, offset: usize) -> String {
+    let gap = "   ";
+    let length = text.chars().count() + gap.len();
+    text.chars()
+        .chain(gap.chars())
+        .cycle()
+        .skip(offset % length)
+        .take(width)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{
+        Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    };
+
+    use super::marquee;
+    use crate::{
+        app::{App, Screen, GAME_OVER_GUARD},
+        high_scores::HighScores,
+        options::Options,
+        world::WorldStatus,
+    };
+
+    /// An app on the main menu
+    fn app() -> App {
+        app_with(&[])
+    }
+
+    /// An app on the main menu, with more options
+    fn app_with(more: &[&str]) -> App {
+        let args = ["--ascii", "--sound", "off"].iter().chain(more);
+        let options = Options::parse(args.map(|arg| arg.to_string())).unwrap();
+        let mut high_scores = HighScores::default();
+        high_scores.insert(120);
+        high_scores.insert(340);
+        let mut app = App::new(40, 14, options, high_scores);
+        press(&mut app, KeyCode::Char('x'));
+        app
+    }
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.input.handle(Event::Key(KeyEvent::from(code)));
+        let frame = app.input.end_frame();
+        app.handle_input(&frame);
+        wait(app, 1);
+    }
+
+    fn mouse(app: &mut App, kind: MouseEventKind, column: u16, row: u16) {
+        app.input.handle(Event::Mouse(MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }));
+        let frame = app.input.end_frame();
+        app.handle_input(&frame);
+        wait(app, 1);
+    }
+
+    fn wait(app: &mut App, frames: u32) {
+        for _ in 0..frames {
+            app.update();
+        }
+        app.draw();
+    }
+
+    #[test]
+    fn marquee_wraps_around() {
+        assert_eq!(marquee("abc", 5, 0), "abc  ");
+        assert_eq!(marquee("abc", 5, 2), "c   a");
+        assert_eq!(marquee("abc", 4, 7), "bc  ");
+    }
+
+    #[test]
+    fn title_blinks_until_a_key() {
+        let options = Options::parse(["--ascii", "--sound", "off"].map(String::from)).unwrap();
+        let mut app = App::new(40, 14, options, HighScores::default());
+        wait(&mut app, 0);
+        assert!(app.canvas.text().contains("Press any key"));
+        wait(&mut app, super::BLINK);
+        assert!(!app.canvas.text().contains("Press any key"));
+
+        press(&mut app, KeyCode::Char('x'));
+        assert_eq!(app.screen, Screen::MainMenu);
+    }
+
+    #[test]
+    fn main_menu_snapshot() {
+        let app = app();
+        let expected = [
+            "",
+            "",
+            "RiverRaid Rust",
+            "",
+            "  > Play",
+            "    High scores",
+            "    Options",
+            "    Credits",
+            "    Quit",
+        ];
+        assert_eq!(app.canvas.text().trim_end(), expected.join("\n"));
+    }
+
+    #[test]
+    fn high_scores_snapshot() {
+        let mut app = app();
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+        let expected = [
+            "",
+            "",
+            "  High scores",
+            "",
+            "   1.    340",
+            "   2.    120",
+            "",
+            "  > Back",
+        ];
+        assert_eq!(app.canvas.text().trim_end(), expected.join("\n"));
+
+        press(&mut app, KeyCode::Enter);
+        assert!(app.canvas.text().contains("> Play"));
+    }
+
+    #[test]
+    fn menus_can_be_clicked() {
+        let mut app = app();
+        // pointing selects, past the label it does not
+        mouse(&mut app, MouseEventKind::Moved, 6, 7);
+        mouse(&mut app, MouseEventKind::Moved, 30, 4);
+        assert!(app.canvas.text().contains("> Credits"));
+        mouse(&mut app, MouseEventKind::Down(MouseButton::Left), 30, 5);
+        assert_eq!(app.screen, Screen::MainMenu);
+
+        mouse(&mut app, MouseEventKind::Down(MouseButton::Left), 4, 5);
+        assert_eq!(app.screen, Screen::HighScores);
+    }
+
+    #[test]
+    fn options_cycle() {
+        let mut app = app();
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+        assert!(app.canvas.text().contains("> Glyphs: ascii"));
+
+        press(&mut app, KeyCode::Enter);
+        assert!(app.canvas.text().contains("> Glyphs: unicode"));
+        assert_eq!(app.options.glyphs.name, "unicode");
+    }
+
+    #[test]
+    fn ctrl_l_redraws_the_screen() {
+        let mut app = app();
+        wait(&mut app, 100);
+        assert!(!app.canvas.is_stale());
+
+        app.input.handle(Event::Key(KeyEvent::new(
+            KeyCode::Char('l'),
+            KeyModifiers::CONTROL,
+        )));
+        let frame = app.input.end_frame();
+        app.handle_input(&frame);
+        assert!(app.canvas.is_stale());
+    }
+
+    #[test]
+    fn redraws_every_so_often() {
+        // every 10 frames of 60 ms
+        let mut app = app_with(&["--redraw-every", "0.6"]);
+        let due: Vec<u32> = (0..30)
+            .filter_map(|_| {
+                app.update();
+                app.healing_due().then_some(app.frame)
+            })
+            .collect();
+        assert_eq!(due.len(), 3);
+        assert!(due.iter().all(|frame| frame % 10 == 0));
+
+        while !app.healing_due() {
+            app.update();
+        }
+        let frame = app.input.end_frame();
+        app.handle_input(&frame);
+        assert!(app.canvas.is_stale());
+    }
+
+    #[test]
+    fn a_smaller_terminal_pauses_the_game() {
+        let mut app = app();
+        press(&mut app, KeyCode::Enter);
+        app.fit((40, 20));
+        assert_eq!(
+            (app.too_small, app.world.status),
+            (None, WorldStatus::Fluent)
+        );
+
+        app.fit((30, 14));
+        assert_eq!(app.too_small, Some((30, 14)));
+        assert_eq!(app.world.status, WorldStatus::Paused);
+
+        // back to a size the game fits in, still paused
+        app.fit((40, 14));
+        assert_eq!(
+            (app.too_small, app.world.status),
+            (None, WorldStatus::Paused)
+        );
+    }
+
+    #[test]
+    fn losing_the_focus_pauses_the_game() {
+        let mut app = app();
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.world.status, WorldStatus::Fluent);
+
+        app.input.handle(Event::FocusLost);
+        let frame = app.input.end_frame();
+        app.handle_input(&frame);
+        assert_eq!(app.world.status, WorldStatus::Paused);
+    }
+
+    #[test]
+    fn quitting_a_game_shows_the_score() {
+        let mut app = app();
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Char('q'));
+        assert!(app.canvas.text().contains("Good Game!"));
+        assert!(app.canvas.text().contains("Score: 0"));
+
+        // keys still hammered at the end of the game don't skip the score
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.screen, Screen::GameOver);
+        assert!(!app.canvas.text().contains("Play again"));
+
+        wait(&mut app, GAME_OVER_GUARD);
+        assert!(app.canvas.text().contains("> Play again"));
+        press(&mut app, KeyCode::Enter);
+        assert!(app.world.player.status == crate::entities::PlayerStatus::Alive);
+    }
+}
@@ -7,7 +7,11 @@ use crossterm::style::{ContentStyle, StyledContent};
 
 use crate::{
     drawable::Drawable,
+    entities::Location,
+    glyphs::{GlyphSet, UNICODE},
+    sprite::Sprite,
     stout_ext::{AsLocationTuple, StdoutExt},
+    theme::Theme,
 };
 
 #[derive(Clone, PartialEq, Eq)]
@@ -39,6 +43,10 @@ pub struct Canvas {
     mac_l: u16,
     table: Vec<Vec<Block>>,
     table_snapshot: Vec<Vec<Block>>,
+    /// The terminal may not show the snapshot, draw every cell next time
+    stale: bool,
+    theme: Theme,
+    glyphs: &'static GlyphSet,
 }
 
 impl Canvas {
@@ -52,14 +60,44 @@ impl Canvas {
             mac_l,
             table: table.clone(),
             table_snapshot: table,
+            stale: false,
+            theme: Theme::default(),
+            glyphs: &UNICODE,
         }
     }
 
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    pub fn set_glyphs(&mut self, glyphs: &'static GlyphSet) {
+        self.glyphs = glyphs;
+    }
+
+    pub fn glyphs(&self) -> &'static GlyphSet {
+        self.glyphs
+    }
+
     pub fn draw(&mut self, drawable: &impl Drawable) -> &mut Canvas {
         drawable.draw(self);
         self
     }
 
+    /// Draw a sprite anchored at `loc`, cells outside of the screen are skipped.
+    pub fn draw_sprite(&mut self, loc: &Location, sprite: &Sprite) -> &mut Canvas {
+        for ((c, l), cell) in sprite.cells(loc) {
+            if c >= 0 && l >= 0 && c < self.max_c as i32 && l < self.mac_l as i32 {
+                self.acquire_block(c as usize, l as usize, cell.character, cell.style);
+            }
+        }
+
+        self
+    }
+
     pub fn draw_line(
         &mut self,
         loc: impl AsLocationTuple,
@@ -76,39 +114,33 @@ impl Canvas {
     ) -> &mut Canvas {
         let (c, l) = loc.as_loc_tuple();
         let string: String = display.into();
+        if l >= self.mac_l {
+            return self;
+        }
 
-        for (offset, ch) in string.chars().enumerate() {
+        let room = self.max_c.saturating_sub(c) as usize;
+        for (offset, ch) in string.chars().take(room).enumerate() {
             self.acquire_block((c as usize) + offset, l as usize, ch, style);
         }
 
         self
     }
 
-    pub fn draw_char(&mut self, loc: impl AsLocationTuple, display: char) -> &mut Canvas {
-        self.draw_styled_char(loc, display, None)
-    }
-
-    pub fn draw_styled_char(
+    /// Draw several lines of text, one under the other.
+    pub fn draw_text(
         &mut self,
         loc: impl AsLocationTuple,
-        display: char,
-        style: impl Into<Option<ContentStyle>>,
+        text: &str,
+        style: impl Into<Option<ContentStyle>> + Copy,
     ) -> &mut Canvas {
         let (c, l) = loc.as_loc_tuple();
-        self.acquire_block(c as usize, l as usize, display, style);
+        for (offset, line) in text.lines().enumerate() {
+            self.draw_styled_line((c, l + offset as u16), line, style);
+        }
 
         self
     }
 
-    pub fn draw_styled<D: Display>(
-        &mut self,
-        loc: impl AsLocationTuple,
-        content: impl Into<StyledContent<D>>,
-    ) -> &mut Canvas {
-        let content: StyledContent<D> = content.into();
-        self.draw_styled_line(loc, content.content().to_string(), Some(*content.style()))
-    }
-
     pub fn clear_all(&mut self) -> &mut Canvas {
         self.table = (0..self.mac_l)
             .map(|_| (0..self.max_c).map(|_| Block::Empty).collect())
@@ -129,11 +161,23 @@ impl Canvas {
         };
     }
 
+    /// Forget what the terminal shows, every cell is drawn on the next
+    /// `draw_map`; for when something else wrote on it
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    /// Draw every cell again, now.
+    pub fn force_redraw(&mut self, stdout: &mut Stdout) -> Result<(), std::io::Error> {
+        self.invalidate();
+        self.draw_map(stdout)
+    }
+
     fn detect_changes(&self) -> Vec<(usize, usize)> {
         let mut changes: Vec<(usize, usize)> = vec![];
         for (l, line) in self.table.iter().enumerate() {
             for (c, block) in line.iter().enumerate() {
-                if block != &self.table_snapshot[l][c] {
+                if self.stale || block != &self.table_snapshot[l][c] {
                     changes.push((c, l))
                 }
             }
@@ -148,19 +192,60 @@ impl Canvas {
             stdout.draw((c as u16, l as u16), &block)?;
             self.table_snapshot[l][c] = block;
         }
+        self.stale = false;
 
         stdout.flush()?;
         Ok(())
     }
 }
 
+#[cfg(test)]
+impl Canvas {
+    /// The characters on the canvas, for snapshot tests.
+    pub fn text(&self) -> String {
+        self.table
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .map(|block| match block {
+                        Block::Empty => ' ',
+                        Block::Acquired { character, .. } => *character,
+                    })
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Every cell is drawn again next time.
+    pub fn is_stale(&self) -> bool {
+        self.stale
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crossterm::style::Stylize;
 
+    use super::Canvas;
+
+    #[test]
+    fn invalidating_redraws_everything() {
+        let mut canvas = Canvas::new(4, 2);
+        canvas.draw_line((1, 1), "ab");
+        assert_eq!(canvas.detect_changes(), [(1, 1), (2, 1)]);
+
+        canvas.table_snapshot = canvas.table.clone();
+        assert!(canvas.detect_changes().is_empty());
+        canvas.invalidate();
+        assert_eq!(canvas.detect_changes().len(), 8);
+    }
+
     #[test]
     fn test_name() {
         let styled_text = "Hello World".red().on_white();
-        println!("{}", styled_text.to_string());
+        println!("{}", styled_text);
     }
 }
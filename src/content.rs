@@ -0,0 +1,458 @@
+//! Content packs: the kinds of entities on the river and their spawners.
+//!
+//! The built-in pack is `content/default.toml`. A pack given with
+//! `--content <dir>` is every `.toml` file of the directory, read over the
+//! built-in pack: its kinds replace the ones of the same name and its
+//! spawners, if it has any, replace all of the built-in ones.
+
+use std::{collections::BTreeMap, fs, io, path::Path, sync::OnceLock};
+
+use serde::Deserialize;
+
+use crate::{
+    glyphs::Glyph,
+    sprite::SpriteArt,
+    theme::Paint,
+    world::{
+        prototypes::Spawner,
+        store::{Art, Collider, CustomArt, Look, Pickup, Prototype, Velocity},
+        Boost, Boosts, Weapon,
+    },
+};
+
+const BUILT_IN: &str = include_str!("../content/default.toml");
+
+/// The kind the plane shoots
+pub const BULLET: &str = "bullet";
+
+#[derive(Clone, Debug)]
+pub struct Content {
+    kinds: BTreeMap<String, Prototype>,
+    pub spawners: Vec<Spawner>,
+}
+
+impl Content {
+    pub fn built_in() -> &'static Content {
+        static BUILT_IN_PACK: OnceLock<Content> = OnceLock::new();
+        BUILT_IN_PACK
+            .get_or_init(|| Content::from_toml(&[]).expect("the built-in content pack is valid"))
+    }
+
+    /// Load the pack in `dir`, over the built-in one.
+    pub fn load(dir: impl AsRef<Path>) -> io::Result<Content> {
+        let dir = dir.as_ref();
+        let error = |err: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid content pack {}: {err}", dir.display()),
+            )
+        };
+
+        let entries = fs::read_dir(dir).map_err(|err| {
+            io::Error::new(
+                err.kind(),
+                format!("cannot read content pack {}: {err}", dir.display()),
+            )
+        })?;
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "toml")
+            })
+            .collect();
+        paths.sort();
+        if paths.is_empty() {
+            return Err(error("no .toml files in it".into()));
+        }
+
+        let mut files = Vec::new();
+        for path in paths {
+            let content = fs::read_to_string(&path).map_err(|err| {
+                io::Error::new(
+                    err.kind(),
+                    format!("cannot read content pack {}: {err}", path.display()),
+                )
+            })?;
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            files.push((name, content));
+        }
+        Content::from_toml(&files).map_err(error)
+    }
+
+    /// Build a pack from `(name, content)` files, read over the built-in one
+    /// in order.
+    pub fn from_toml(files: &[(String, String)]) -> Result<Content, String> {
+        let mut kinds = BTreeMap::new();
+        let mut spawners = Vec::new();
+        let mut replacing = Vec::new();
+        let built_in = ("built-in pack".to_string(), BUILT_IN.to_string());
+        for (index, (name, content)) in [built_in].iter().chain(files).enumerate() {
+            let file: PackFile = toml::from_str(content).map_err(|err| format!("{name}: {err}"))?;
+
+            let mut seen = Vec::new();
+            for kind in file.kind {
+                if seen.contains(&kind.name) {
+                    return Err(format!("{name}: kind `{}` is defined twice", kind.name));
+                }
+                seen.push(kind.name.clone());
+                let prototype = kind
+                    .prototype()
+                    .map_err(|err| format!("{name}: kind `{}`: {err}", kind.name))?;
+                kinds.insert(kind.name, prototype);
+            }
+
+            let target = if index == 0 {
+                &mut spawners
+            } else {
+                &mut replacing
+            };
+            for (number, spawner) in file.spawner.into_iter().enumerate() {
+                target.push((format!("{name}: spawner {}", number + 1), spawner));
+            }
+        }
+        // the spawners of the pack replace the built-in ones
+        if !replacing.is_empty() {
+            spawners = replacing;
+        }
+
+        let bullet = kinds.get(BULLET).ok_or(format!(
+            "the `{BULLET}` kind is needed, for the shots of the plane"
+        ))?;
+        let bullet_only_looks = bullet.collider.is_none()
+            && bullet.velocity.is_none()
+            && bullet.health.is_none()
+            && bullet.lifetime.is_none()
+            && bullet.score.is_none();
+        if !bullet_only_looks {
+            return Err(format!(
+                "the `{BULLET}` kind only has a look, the weapons give the rest"
+            ));
+        }
+        let spawners = spawners
+            .into_iter()
+            .map(|(name, spawner)| {
+                spawner
+                    .spawner(&kinds)
+                    .map_err(|err| format!("{name}: {err}"))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Content { kinds, spawners })
+    }
+
+    pub fn kind(&self, name: &str) -> Option<&Prototype> {
+        self.kinds.get(name)
+    }
+
+    /// The names of the kinds a level can place, all but the bullet
+    pub fn placeable(&self) -> impl Iterator<Item = &str> {
+        self.kinds
+            .keys()
+            .map(String::as_str)
+            .filter(|&name| name != BULLET)
+    }
+
+    /// How the shots of the plane look
+    pub fn bullet(&self) -> Look {
+        self.kind(BULLET).expect("packs have a bullet").sprite
+    }
+} // end of Content implementation.
+
+/// A file of a pack.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PackFile {
+    #[serde(default)]
+    kind: Vec<KindFile>,
+    #[serde(default)]
+    spawner: Vec<SpawnerFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KindFile {
+    name: String,
+    art: ArtFile,
+    wreck: Option<ArtFile>,
+    color: String,
+    #[serde(default)]
+    layer: u8,
+    #[serde(default)]
+    trail: bool,
+    #[serde(default)]
+    hostile: bool,
+    gas: Option<u16>,
+    weapon: Option<String>,
+    boost: Option<String>,
+    health: Option<u16>,
+    score: Option<u16>,
+    velocity: Option<(i16, i16)>,
+    lifetime: Option<u16>,
+}
+
+impl KindFile {
+    fn prototype(&self) -> Result<Prototype, String> {
+        let paint = Paint::parse(&self.color).ok_or(format!(
+            "unknown colour `{}`, use a colour or enemy, fuel, bullet, player or powerup",
+            self.color
+        ))?;
+        let look = Look {
+            wreck: self.wreck.as_ref().map(ArtFile::art).transpose()?,
+            layer: self.layer,
+            trail: self.trail,
+            ..Look::new(self.art.art()?, paint)
+        };
+
+        let mut colliders = Vec::new();
+        if self.hostile {
+            colliders.push(Collider::Hostile);
+        }
+        if let Some(gas) = self.gas {
+            colliders.push(Collider::Depot { gas });
+        }
+        if let Some(weapon) = &self.weapon {
+            let weapon = Weapon::parse(weapon)
+                .filter(|weapon| *weapon != Weapon::Single)
+                .ok_or(format!("unknown weapon `{weapon}`, use rapid or spread"))?;
+            colliders.push(Collider::Pickup(Pickup::Weapon(weapon)));
+        }
+        if let Some(boost) = &self.boost {
+            let boost = Boost::parse(boost).ok_or(format!(
+                "unknown boost `{boost}`, use shield, multiplier or slow_time"
+            ))?;
+            colliders.push(Collider::Pickup(Pickup::Boost(boost)));
+        }
+        if colliders.len() > 1 {
+            return Err("it is only one of hostile, a depot (gas), a weapon or a boost".into());
+        }
+
+        if self.health == Some(0) {
+            return Err("`health` must be above 0, leave it out for what cannot be shot".into());
+        }
+        if self.score.is_some() && self.health.is_none() {
+            return Err("`score` is for shooting it down, it needs `health`".into());
+        }
+        // doubled by the multiplier, it must still fit the score
+        let most = u16::MAX / Boosts::MULTIPLIER;
+        if self.score.is_some_and(|score| score > most) {
+            return Err(format!("`score` is at most {most}"));
+        }
+        if self.lifetime.is_some() && self.velocity.is_none() {
+            return Err("`lifetime` is for what flies, it needs a `velocity`".into());
+        }
+
+        Ok(Prototype {
+            velocity: self.velocity.map(|(c, l)| Velocity { c, l }),
+            collider: colliders.pop(),
+            health: self.health,
+            lifetime: self.lifetime,
+            score: self.score,
+            ..Prototype::new(look)
+        })
+    }
+}
+
+/// A sprite of the glyph set by name, or art of its own.
+#[derive(Deserialize)]
+#[serde(
+    untagged,
+    expecting = "expected the name of a sprite or a table with `rows`"
+)]
+enum ArtFile {
+    Glyph(String),
+    Custom(CustomArtFile),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CustomArtFile {
+    rows: Vec<String>,
+    /// For the ASCII glyph set, `rows` when they are plain ASCII
+    ascii: Option<Vec<String>>,
+    #[serde(default)]
+    anchor: (u16, u16),
+}
+
+impl ArtFile {
+    fn art(&self) -> Result<Art, String> {
+        match self {
+            ArtFile::Glyph(name) => Glyph::by_name(name)
+                .map(Art::Glyph)
+                .ok_or(format!("unknown art `{name}`, use {}", Glyph::names())),
+            ArtFile::Custom(custom) => {
+                let ascii = match &custom.ascii {
+                    Some(ascii) => ascii,
+                    None if custom.rows.iter().all(|row| row.is_ascii()) => &custom.rows,
+                    None => return Err("the art needs `ascii` rows too".into()),
+                };
+                if !ascii.iter().all(|row| row.is_ascii()) {
+                    return Err("the `ascii` rows of the art are not plain ASCII".into());
+                }
+
+                // packs are loaded once, their art lasts as long as the game
+                let art = CustomArt {
+                    unicode: sprite_art(&custom.rows, custom.anchor)?,
+                    ascii: sprite_art(ascii, custom.anchor)?,
+                };
+                Ok(Art::Custom(Box::leak(Box::new(art))))
+            }
+        }
+    }
+}
+
+fn sprite_art(rows: &[String], anchor: (u16, u16)) -> Result<SpriteArt, String> {
+    let width = rows
+        .iter()
+        .map(|row| row.chars().count())
+        .max()
+        .unwrap_or(0);
+    if width == 0 {
+        return Err("the art has no rows".into());
+    }
+    if anchor.0 as usize >= width || anchor.1 as usize >= rows.len() {
+        return Err(format!("the anchor {anchor:?} is out of the art"));
+    }
+
+    let rows: Vec<&'static str> = rows
+        .iter()
+        .map(|row| &*Box::leak(row.clone().into_boxed_str()))
+        .collect();
+    Ok(SpriteArt {
+        rows: Box::leak(rows.into_boxed_slice()),
+        anchor,
+    })
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SpawnerFile {
+    odds: u32,
+    #[serde(default)]
+    margin: u16,
+    kinds: BTreeMap<String, u32>,
+}
+
+impl SpawnerFile {
+    fn spawner(self, kinds: &BTreeMap<String, Prototype>) -> Result<Spawner, String> {
+        if self.odds == 0 {
+            return Err("`odds` are one in that many rows, it must be above 0".into());
+        }
+
+        let mut table = Vec::new();
+        for (name, weight) in self.kinds {
+            if name == BULLET {
+                return Err(format!(
+                    "`{BULLET}` is shot by the plane, it cannot be spawned"
+                ));
+            }
+            let prototype = kinds.get(&name).ok_or_else(|| {
+                let names: Vec<&str> = kinds.keys().map(String::as_str).collect();
+                format!("unknown kind `{name}`, the kinds are {}", names.join(", "))
+            })?;
+            table.push((prototype.clone(), weight));
+        }
+        if table.iter().all(|(_, weight)| *weight == 0) {
+            return Err("the weights of its kinds add up to 0".into());
+        }
+
+        Ok(Spawner {
+            odds: self.odds,
+            margin: self.margin,
+            table,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pack(content: &str) -> Result<Content, String> {
+        Content::from_toml(&[("mine.toml".into(), content.into())])
+    }
+
+    #[test]
+    fn the_built_in_pack_is_the_game() {
+        let content = Content::built_in();
+        let enemy = content.kind("enemy").unwrap();
+        assert_eq!(enemy.collider, Some(Collider::Hostile));
+        assert_eq!((enemy.health, enemy.score), (Some(2), Some(10)));
+        let depot = content.kind("depot").unwrap();
+        assert_eq!(depot.collider, Some(Collider::Depot { gas: 400 }));
+        assert_eq!(depot.score, Some(20));
+
+        let odds: Vec<u32> = content
+            .spawners
+            .iter()
+            .map(|spawner| spawner.odds)
+            .collect();
+        assert_eq!(odds, [10, 100, 250, 300]);
+    }
+
+    #[test]
+    fn packs_replace_kinds_and_spawners() {
+        let content = pack(
+            r##"
+            [[kind]]
+            name = "enemy"
+            art = { rows = ["<o>"], anchor = [1, 0] }
+            color = "#ff8000"
+            hostile = true
+            health = 4
+            score = 50
+
+            [[kind]]
+            name = "mine"
+            art = { rows = ["✹"], ascii = ["*"] }
+            color = "enemy"
+            hostile = true
+
+            [[spawner]]
+            odds = 20
+            kinds = { enemy = 2, mine = 1 }
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(content.kind("enemy").unwrap().score, Some(50));
+        assert!(content.kind("depot").is_some());
+        assert_eq!(content.spawners.len(), 1);
+        assert_eq!(content.spawners[0].table.len(), 2);
+    }
+
+    #[test]
+    fn validation_errors_say_where() {
+        let error = |content| pack(content).unwrap_err();
+
+        assert_eq!(
+            error("[[kind]]\nname = \"rock\"\nart = \"rok\"\ncolor = \"enemy\""),
+            format!(
+                "mine.toml: kind `rock`: unknown art `rok`, use {}",
+                Glyph::names()
+            )
+        );
+        assert!(
+            error("[[kind]]\nname = \"rock\"\nart = \"enemy\"\ncolor = \"mauve\"")
+                .starts_with("mine.toml: kind `rock`: unknown colour `mauve`")
+        );
+        assert_eq!(
+            error("[[kind]]\nname = \"rock\"\nart = \"enemy\"\ncolor = \"enemy\"\nscore = 5"),
+            "mine.toml: kind `rock`: `score` is for shooting it down, it needs `health`"
+        );
+        assert_eq!(
+            error("[[kind]]\nname = \"rock\"\nart = \"enemy\"\ncolor = \"enemy\"\nhealth = 1\nscore = 40000"),
+            "mine.toml: kind `rock`: `score` is at most 32767"
+        );
+        assert!(error("[[spawner]]\nodds = 5\nkinds = { rock = 1 }")
+            .starts_with("mine.toml: spawner 1: unknown kind `rock`"));
+        assert!(
+            error("[[spawner]]\nodds = 5\nkinds = { enemy = 1 }\nrate = 2")
+                .starts_with("mine.toml: TOML parse error")
+        );
+        assert!(
+            error("[[kind]]\nname = \"mine\"\nart = { rows = [\"✹\"] }\ncolor = \"enemy\"")
+                .ends_with("the art needs `ascii` rows too")
+        );
+    }
+}
@@ -1,51 +1,65 @@
-use crossterm::style::{ContentStyle, Stylize};
+use crossterm::style::{Attribute, Stylize};
 
 use crate::{
     canvas::Canvas,
-    entities::{Bullet, Enemy, EntityStatus, Fuel, Player},
+    entities::{EntityStatus, Location, Player},
+    glyphs::GlyphSet,
+    sprite::Sprite,
+    theme::Theme,
+    world::store::{Collider, EntityRef},
 };
 
 pub trait Drawable {
-    fn draw(&self, sc: &mut Canvas);
-}
+    fn location(&self) -> &Location;
+
+    /// The sprite of the entity, `None` when there is nothing to show.
+    fn sprite(&self, glyphs: &GlyphSet, theme: &Theme) -> Option<Sprite>;
 
-impl Drawable for Enemy {
     fn draw(&self, sc: &mut Canvas) {
-        match self.status {
-            EntityStatus::Alive => {
-                sc.draw_styled_char(self, '☠', ContentStyle::new().red().on_blue());
-            }
-            EntityStatus::DeadBody => {
-                sc.draw_styled(self, '☢'.red().on_blue());
-            }
-            EntityStatus::Dead => {}
-        };
+        if let Some(sprite) = self.sprite(sc.glyphs(), sc.theme()) {
+            sc.draw_sprite(self.location(), &sprite);
+        }
     }
 }
 
-impl Drawable for Fuel {
-    fn draw(&self, sc: &mut Canvas) {
-        match self.status {
-            EntityStatus::Alive => {
-                sc.draw_styled_char(self, '❤', ContentStyle::new().yellow().on_blue());
-            }
-            EntityStatus::DeadBody => {
-                sc.draw_styled(self, '❂'.yellow().on_blue());
-            }
-            EntityStatus::Dead => {}
-        };
+impl Drawable for EntityRef<'_> {
+    fn location(&self) -> &Location {
+        &self.store.position[self.entity]
     }
-}
 
-impl Drawable for Bullet {
-    fn draw(&self, sc: &mut Canvas) {
-        sc.draw_styled_char(self, '⇈', ContentStyle::new().cyan().on_blue())
-            .draw_styled_char((self.location.c, self.location.l - 1), '↟', ContentStyle::new().cyan().on_blue());
+    fn sprite(&self, glyphs: &GlyphSet, theme: &Theme) -> Option<Sprite> {
+        let look = &self.store.sprite[self.entity];
+        let status = self.store.status[self.entity];
+        let art = match status {
+            EntityStatus::Alive => look.art,
+            EntityStatus::DeadBody => look.wreck?,
+            EntityStatus::Dead => return None,
+        };
+
+        let mut style = theme.on_water(theme.paint(look.paint));
+        // drained, only worth shooting
+        if status == EntityStatus::Alive
+            && self.store.collider[self.entity] == Some(Collider::Depot { gas: 0 })
+        {
+            style = style.attribute(Attribute::Dim);
+        }
+
+        let sprite = Sprite::new(art.sprite_art(glyphs), style);
+        if look.trail && status == EntityStatus::Alive {
+            // the tail fades out
+            Some(sprite.with_cell_style(0, 0, style.attribute(Attribute::Dim)))
+        } else {
+            Some(sprite)
+        }
     }
 }
 
 impl Drawable for Player {
-    fn draw(&self, sc: &mut Canvas) {
-        sc.draw_styled(self, '▲'.white().on_blue());
+    fn location(&self) -> &Location {
+        &self.location
+    }
+
+    fn sprite(&self, glyphs: &GlyphSet, theme: &Theme) -> Option<Sprite> {
+        Some(Sprite::new(&glyphs.player, theme.on_water(theme.player)))
     }
 }
@@ -1,17 +1,20 @@
-#[derive(PartialEq, Eq)]
+use crate::world::Weapon;
+
+#[derive(PartialEq, Eq, Debug)]
 pub enum DeathCause {
     Enemy,
     Ground,
     Fuel,
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(PartialEq, Eq, Debug)]
 pub enum PlayerStatus {
     Dead(DeathCause),
     Alive,
     Quit,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum EntityStatus {
     Alive,
     DeadBody,
@@ -28,73 +31,24 @@ impl Location {
     pub fn new(c: u16, l: u16) -> Self {
         Location { c, l }
     }
-
-    // Checks if two locations are within a specified margin of each other
-    pub fn hit_with_margin(
-        &self,
-        other: &Location,
-        top: u16,
-        right: u16,
-        bottom: u16,
-        left: u16,
-    ) -> bool {
-        (other.l > self.l || self.l - other.l <= bottom)
-            && (self.l > other.l || other.l - self.l <= top)
-            && (other.c > self.c || self.c - other.c <= left)
-            && (self.c > other.c || other.c - self.c <= right)
-    }
-
-    // check if two locations is point to the same location
-    pub fn hit(&self, other: &Location) -> bool {
-        self.hit_with_margin(other, 0, 0, 0, 0)
-    }
 } // end of Location implementation.
 
-pub struct Enemy {
-    pub location: Location,
-    pub status: EntityStatus,
-}
-
-impl Enemy {
-    pub fn new(column: u16, line: u16, status: EntityStatus) -> Enemy {
-        Enemy {
-            location: Location::new(column, line),
-            status,
-        }
-    }
-} // end of Enemy implementation.
-
-pub struct Bullet {
-    pub location: Location,
-    pub energy: u16,
-}
-
-impl Bullet {
-    pub fn new(column: u16, line: u16, energy: u16) -> Bullet {
-        Bullet {
-            location: Location::new(column, line),
-            energy,
-        }
-    }
-} // end of Bullet implementation.
-
-pub struct Fuel {
-    pub location: Location,
-    pub status: EntityStatus,
-}
-
-impl Fuel {
-    pub fn new(column: u16, line: u16, status: EntityStatus) -> Fuel {
-        Fuel {
-            location: Location::new(column, line),
-            status,
-        }
-    }
-} // end of Fuel implementation.
-
 pub struct Player {
     pub location: Location,
     pub status: PlayerStatus,
     pub gas: u16,
+    /// Size of the tank
+    pub max_gas: u16,
     pub score: u16,
+    pub weapon: Weapon,
+    /// Shots left with the weapon
+    pub ammo: u16,
+    /// Frames before the weapon can shoot again
+    pub cooldown: u16,
+    /// Directions held, from -1 to 1 on each axis
+    pub steering: (i8, i8),
+    /// Cells a frame
+    pub velocity: (f32, f32),
+    /// The part of a cell moved, but not drawn yet
+    pub offset: (f32, f32),
 }
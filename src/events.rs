@@ -1,70 +1,93 @@
-use crossterm::event::{poll, read, Event, KeyCode, KeyEventKind};
-
-use std::time::Duration;
-
 use crate::{
-    entities::{Bullet, PlayerStatus},
-    world::World,
+    entities::PlayerStatus,
+    input::{Action, Frame},
+    world::{World, WorldStatus},
 };
 
-pub fn handle_pressed_keys(world: &mut World) {
-    if poll(Duration::from_millis(10)).unwrap() {
-        let key = read().unwrap();
+/// Play the actions of a frame on the world, the plane goes diagonal when
+/// two directions are held. Without a steering key held, it steers toward
+/// the mouse pointer.
+pub fn handle_actions(world: &mut World, frame: &Frame) {
+    if frame.pressed(Action::Quit) {
+        world.player.status = PlayerStatus::Quit;
+        return;
+    }
+    if frame.pressed(Action::Pause) {
+        world.status = match world.status {
+            WorldStatus::Fluent => WorldStatus::Paused,
+            WorldStatus::Paused => WorldStatus::Fluent,
+        };
+    }
+    if world.status == WorldStatus::Paused || world.player.status != PlayerStatus::Alive {
+        return;
+    }
 
-        while poll(Duration::from_millis(0)).unwrap() {
-            let _ = read();
-        }
+    // the plane itself moves with the physics
+    let axis = |less, more| frame.held(more) as i8 - frame.held(less) as i8;
+    let mut steering = (
+        axis(Action::Left, Action::Right),
+        axis(Action::Up, Action::Down),
+    );
+    if let (0, Some(column)) = (steering.0, frame.pointer) {
+        steering.0 = (column as i32 - world.player.location.c as i32).signum() as i8;
+    }
+    world.player.steering = steering;
+    if frame.held(Action::Fire) {
+        world.fire();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{
+        Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    };
+
+    use super::*;
+    use crate::{input::Input, world::store::Collider};
+
+    #[test]
+    fn moves_diagonally_while_firing() {
+        let mut world = World::new(40, 20);
+        let start = (world.player.location.c, world.player.location.l);
 
-        match key {
-            Event::Key(event) => {
-                // I'm reading from keyboard into event
-                match event.code {
-                    KeyCode::Char('w') | KeyCode::Up
-                        if world.player.status == PlayerStatus::Alive
-                            && world.player.location.l > 1 =>
-                    {
-                        world.player.location.l -= 1
-                    }
-                    KeyCode::Char('s') | KeyCode::Down
-                        if world.player.status == PlayerStatus::Alive
-                            && world.player.location.l < world.maxl - 1 =>
-                    {
-                        world.player.location.l += 1
-                    }
-                    KeyCode::Char('a') | KeyCode::Left
-                        if world.player.status == PlayerStatus::Alive
-                            && world.player.location.c > 1 =>
-                    {
-                        world.player.location.c -= 1
-                    }
-                    KeyCode::Char('d') | KeyCode::Right
-                        if world.player.status == PlayerStatus::Alive
-                            && world.player.location.c < world.maxc - 1 =>
-                    {
-                        world.player.location.c += 1
-                    }
-                    KeyCode::Char('q') => world.player.status = PlayerStatus::Quit,
-                    KeyCode::Char('p') if event.kind == KeyEventKind::Press => {
-                        use crate::WorldStatus::*;
-                        world.status = match world.status {
-                            Fluent => Paused,
-                            Paused => Fluent,
-                        };
-                    }
-                    KeyCode::Char(' ') => {
-                        if world.player.status == PlayerStatus::Alive && world.bullets.is_empty() {
-                            let new_bullet = Bullet::new(
-                                world.player.location.c,
-                                world.player.location.l - 1,
-                                world.maxl / 4,
-                            );
-                            world.bullets.push(new_bullet);
-                        }
-                    }
-                    _ => {}
-                }
-            }
-            _ => {}
+        let mut input = Input::default();
+        for code in [KeyCode::Up, KeyCode::Left, KeyCode::Char(' ')] {
+            input.handle(Event::Key(KeyEvent::from(code)));
         }
+        handle_actions(&mut world, &input.end_frame());
+        world.physics();
+
+        assert_eq!(world.player.location.c, start.0 - 1);
+        assert_eq!(world.player.location.l, start.1 - 1);
+        let bullets = world
+            .entities
+            .collider
+            .iter()
+            .filter(|collider| matches!(collider, Some(Collider::Projectile { .. })));
+        assert_eq!(bullets.count(), 1);
+    }
+
+    #[test]
+    fn steers_toward_the_pointer() {
+        let mut world = World::new(40, 20);
+        let c = world.player.location.c;
+        let mut input = Input::default();
+        input.handle(Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: c - 5,
+            row: 3,
+            modifiers: KeyModifiers::NONE,
+        }));
+        handle_actions(&mut world, &input.end_frame());
+        assert_eq!(world.player.steering, (-1, 0));
+
+        // there already, the keys still win
+        world.player.location.c = c - 5;
+        handle_actions(&mut world, &input.end_frame());
+        assert_eq!(world.player.steering, (0, 0));
+        input.handle(Event::Key(KeyEvent::from(KeyCode::Right)));
+        handle_actions(&mut world, &input.end_frame());
+        assert_eq!(world.player.steering, (1, 0));
     }
 }
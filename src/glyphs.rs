@@ -0,0 +1,362 @@
+//! Characters used to draw the sprites and the banners.
+//!
+//! The [`UNICODE`] set looks best, the [`ASCII`] set is for the Linux console
+//! and terminals without a good unicode font.
+
+use std::env;
+
+use crate::sprite::SpriteArt;
+
+/// A big banner with a plain text fallback for narrow screens.
+pub struct Banner {
+    pub art: Option<&'static str>,
+    /// Minimum screen width needed to show the art.
+    pub width: u16,
+    pub text: &'static str,
+}
+
+impl Banner {
+    const fn text(text: &'static str) -> Banner {
+        Banner {
+            art: None,
+            width: 0,
+            text,
+        }
+    }
+
+    /// The art if it fits in `maxc` columns, otherwise the text.
+    pub fn fit(&self, maxc: u16) -> &'static str {
+        match self.art {
+            Some(art) if maxc > self.width => art,
+            _ => self.text,
+        }
+    }
+}
+
+pub struct GlyphSet {
+    pub name: &'static str,
+    pub enemy: SpriteArt,
+    pub enemy_dead: SpriteArt,
+    pub fuel: SpriteArt,
+    pub fuel_dead: SpriteArt,
+    pub bullet: SpriteArt,
+    pub ammo_rapid: SpriteArt,
+    pub ammo_spread: SpriteArt,
+    pub shield: SpriteArt,
+    pub multiplier: SpriteArt,
+    pub slow_time: SpriteArt,
+    pub bridge: SpriteArt,
+    pub player: SpriteArt,
+    /// Debris of explosions, from fresh to almost gone.
+    pub debris: &'static [char],
+    /// Fills of a gauge cell, the last one is full
+    pub gauge: &'static [char],
+    pub pause_box: [&'static str; 3],
+    pub title: Banner,
+    pub good_game: Banner,
+    pub thanks: Banner,
+    pub crashed: Banner,
+    pub killed: Banner,
+    pub no_fuel: Banner,
+}
+
+pub const UNICODE: GlyphSet = GlyphSet {
+    name: "unicode",
+    enemy: SpriteArt::single(&["☠"]),
+    enemy_dead: SpriteArt::single(&["☢"]),
+    fuel: SpriteArt {
+        rows: &["╔F╗", "║U║", "║E║", "╚L╝"],
+        anchor: (1, 0),
+    },
+    fuel_dead: SpriteArt::single(&["❂"]),
+    bullet: SpriteArt {
+        rows: &["↟", "⇈"],
+        anchor: (0, 1),
+    },
+    ammo_rapid: SpriteArt {
+        rows: &["‹R›"],
+        anchor: (1, 0),
+    },
+    ammo_spread: SpriteArt {
+        rows: &["‹S›"],
+        anchor: (1, 0),
+    },
+    shield: SpriteArt {
+        rows: &["(◆)"],
+        anchor: (1, 0),
+    },
+    multiplier: SpriteArt {
+        rows: &["(×)"],
+        anchor: (1, 0),
+    },
+    slow_time: SpriteArt {
+        rows: &["(◷)"],
+        anchor: (1, 0),
+    },
+    bridge: SpriteArt::single(&["═"]),
+    player: SpriteArt::single(&["▲"]),
+    debris: &['✸', '✦', '•', '·'],
+    gauge: &['▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'],
+    pause_box: ["╔═══════════╗", "║Game Paused║", "╚═══════════╝"],
+    title: Banner {
+        art: Some("██████╗ ██╗██╗   ██╗███████╗██████╗ ██████╗  █████╗ ██╗██████╗     ██████╗ ██╗   ██╗███████╗████████╗\n██╔══██╗██║██║   ██║██╔════╝██╔══██╗██╔══██╗██╔══██╗██║██╔══██╗    ██╔══██╗██║   ██║██╔════╝╚══██╔══╝\n██████╔╝██║██║   ██║█████╗  ██████╔╝██████╔╝███████║██║██║  ██║    ██████╔╝██║   ██║███████╗   ██║   \n██╔══██╗██║╚██╗ ██╔╝██╔══╝  ██╔══██╗██╔══██╗██╔══██║██║██║  ██║    ██╔══██╗██║   ██║╚════██║   ██║   \n██║  ██║██║ ╚████╔╝ ███████╗██║  ██║██║  ██║██║  ██║██║██████╔╝    ██║  ██║╚██████╔╝███████║   ██║   \n╚═╝  ╚═╝╚═╝  ╚═══╝  ╚══════╝╚═╝  ╚═╝╚═╝  ╚═╝╚═╝  ╚═╝╚═╝╚═════╝     ╚═╝  ╚═╝ ╚═════╝ ╚══════╝   ╚═╝   "),
+        width: 100,
+        text: "RiverRaid Rust",
+    },
+    good_game: Banner {
+        art: Some(" ██████╗  ██████╗  ██████╗ ██████╗      ██████╗  █████╗ ███╗   ███╗███████╗██╗\n██╔════╝ ██╔═══██╗██╔═══██╗██╔══██╗    ██╔════╝ ██╔══██╗████╗ ████║██╔════╝██║\n██║  ███╗██║   ██║██║   ██║██║  ██║    ██║  ███╗███████║██╔████╔██║█████╗  ██║\n██║   ██║██║   ██║██║   ██║██║  ██║    ██║   ██║██╔══██║██║╚██╔╝██║██╔══╝  ╚═╝\n╚██████╔╝╚██████╔╝╚██████╔╝██████╔╝    ╚██████╔╝██║  ██║██║ ╚═╝ ██║███████╗██╗\n ╚═════╝  ╚═════╝  ╚═════╝ ╚═════╝      ╚═════╝ ╚═╝  ╚═╝╚═╝     ╚═╝╚══════╝╚═╝"),
+        width: 0,
+        text: "Good Game!",
+    },
+    thanks: Banner {
+        art: Some("████████╗██╗  ██╗ █████╗ ███╗   ██╗██╗  ██╗███████╗\n╚══██╔══╝██║  ██║██╔══██╗████╗  ██║██║ ██╔╝██╔════╝\n   ██║   ███████║███████║██╔██╗ ██║█████╔╝ ███████╗\n   ██║   ██╔══██║██╔══██║██║╚██╗██║██╔═██╗ ╚════██║\n   ██║   ██║  ██║██║  ██║██║ ╚████║██║  ██╗███████║██╗\n   ╚═╝   ╚═╝  ╚═╝╚═╝  ╚═╝╚═╝  ╚═══╝╚═╝  ╚═╝╚══════╝╚═╝"),
+        width: 0,
+        text: "Thanks!",
+    },
+    crashed: Banner {
+        art: Some("█▄█ █▀█ █░█   █▀▀ █▀█ ▄▀█ █▀ █░█ █▀▀ █▀▄   █ █▄░█   ▀█▀ █░█ █▀▀   █▀▀ █▀█ █▀█ █░█ █▄░█ █▀▄ ░\n░█░ █▄█ █▄█   █▄▄ █▀▄ █▀█ ▄█ █▀█ ██▄ █▄▀   █ █░▀█   ░█░ █▀█ ██▄   █▄█ █▀▄ █▄█ █▄█ █░▀█ █▄▀ ▄"),
+        width: 91,
+        text: "You crashed in the ground.",
+    },
+    killed: Banner {
+        art: Some("▄▀█ █▄░█   █▀▀ █▄░█ █▀▀ █▀▄▀█ █▄█   █▄▀ █ █░░ █░░ █▀▀ █▀▄   █▄█ █▀█ █░█ ░\n█▀█ █░▀█   ██▄ █░▀█ ██▄ █░▀░█ ░█░   █░█ █ █▄▄ █▄▄ ██▄ █▄▀   ░█░ █▄█ █▄█ ▄"),
+        width: 72,
+        text: "An enemy killed you.",
+    },
+    no_fuel: Banner {
+        art: Some("█▄█ █▀█ █░█   █▀█ ▄▀█ █▄░█   █▀█ █░█ ▀█▀   █▀█ █▀▀   █▀▀ █░█ █▀▀ █░░ ░\n░█░ █▄█ █▄█   █▀▄ █▀█ █░▀█   █▄█ █▄█ ░█░   █▄█ █▀░   █▀░ █▄█ ██▄ █▄▄ ▄"),
+        width: 69,
+        text: "You ran out of fuel.",
+    },
+};
+
+pub const ASCII: GlyphSet = GlyphSet {
+    name: "ascii",
+    enemy: SpriteArt::single(&["E"]),
+    enemy_dead: SpriteArt::single(&["*"]),
+    fuel: SpriteArt {
+        rows: &["+F+", "|U|", "|E|", "+L+"],
+        anchor: (1, 0),
+    },
+    fuel_dead: SpriteArt::single(&["+"]),
+    bullet: SpriteArt {
+        rows: &["|", "^"],
+        anchor: (0, 1),
+    },
+    ammo_rapid: SpriteArt {
+        rows: &["<R>"],
+        anchor: (1, 0),
+    },
+    ammo_spread: SpriteArt {
+        rows: &["<S>"],
+        anchor: (1, 0),
+    },
+    shield: SpriteArt {
+        rows: &["(#)"],
+        anchor: (1, 0),
+    },
+    multiplier: SpriteArt {
+        rows: &["(x)"],
+        anchor: (1, 0),
+    },
+    slow_time: SpriteArt {
+        rows: &["(~)"],
+        anchor: (1, 0),
+    },
+    bridge: SpriteArt::single(&["="]),
+    player: SpriteArt::single(&["P"]),
+    debris: &['#', '*', '+', '.'],
+    gauge: &['-', '='],
+    pause_box: ["+-----------+", "|Game Paused|", "+-----------+"],
+    title: Banner::text("RiverRaid Rust"),
+    good_game: Banner::text("Good Game!"),
+    thanks: Banner::text("Thanks!"),
+    crashed: Banner::text("You crashed in the ground."),
+    killed: Banner::text("An enemy killed you."),
+    no_fuel: Banner::text("You ran out of fuel."),
+};
+
+/// The sprites of the glyph sets that content packs can use.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Glyph {
+    Enemy,
+    EnemyDead,
+    Fuel,
+    FuelDead,
+    Bullet,
+    AmmoRapid,
+    AmmoSpread,
+    Shield,
+    Multiplier,
+    SlowTime,
+    Bridge,
+}
+
+impl Glyph {
+    const ALL: [Glyph; 11] = [
+        Glyph::Enemy,
+        Glyph::EnemyDead,
+        Glyph::Fuel,
+        Glyph::FuelDead,
+        Glyph::Bullet,
+        Glyph::AmmoRapid,
+        Glyph::AmmoSpread,
+        Glyph::Shield,
+        Glyph::Multiplier,
+        Glyph::SlowTime,
+        Glyph::Bridge,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Glyph::Enemy => "enemy",
+            Glyph::EnemyDead => "enemy_dead",
+            Glyph::Fuel => "fuel",
+            Glyph::FuelDead => "fuel_dead",
+            Glyph::Bullet => "bullet",
+            Glyph::AmmoRapid => "ammo_rapid",
+            Glyph::AmmoSpread => "ammo_spread",
+            Glyph::Shield => "shield",
+            Glyph::Multiplier => "multiplier",
+            Glyph::SlowTime => "slow_time",
+            Glyph::Bridge => "bridge",
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Glyph> {
+        Glyph::ALL.into_iter().find(|glyph| glyph.name() == name)
+    }
+
+    /// The names, for error messages
+    pub fn names() -> String {
+        Glyph::ALL.map(Glyph::name).join(", ")
+    }
+}
+
+impl GlyphSet {
+    pub fn art(&self, glyph: Glyph) -> &SpriteArt {
+        match glyph {
+            Glyph::Enemy => &self.enemy,
+            Glyph::EnemyDead => &self.enemy_dead,
+            Glyph::Fuel => &self.fuel,
+            Glyph::FuelDead => &self.fuel_dead,
+            Glyph::Bullet => &self.bullet,
+            Glyph::AmmoRapid => &self.ammo_rapid,
+            Glyph::AmmoSpread => &self.ammo_spread,
+            Glyph::Shield => &self.shield,
+            Glyph::Multiplier => &self.multiplier,
+            Glyph::SlowTime => &self.slow_time,
+            Glyph::Bridge => &self.bridge,
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<&'static GlyphSet> {
+        match name {
+            "unicode" => Some(&UNICODE),
+            "ascii" => Some(&ASCII),
+            "auto" => Some(GlyphSet::detect()),
+            _ => None,
+        }
+    }
+
+    /// Fall back to ASCII on the Linux console and on non UTF-8 locales.
+    pub fn detect() -> &'static GlyphSet {
+        GlyphSet::detect_from(|name| env::var(name).ok())
+    }
+
+    /// [`GlyphSet::detect`] with the environment variables read by `var`
+    fn detect_from(var: impl Fn(&str) -> Option<String>) -> &'static GlyphSet {
+        let term = var("TERM").unwrap_or_default();
+        if term == "linux" || term == "dumb" {
+            return &ASCII;
+        }
+
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .into_iter()
+            .filter_map(&var)
+            .find(|value| !value.is_empty());
+
+        match locale {
+            Some(locale) if !locale.to_lowercase().replace('-', "").contains("utf8") => &ASCII,
+            _ => &UNICODE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(vars: &[(&str, &str)]) -> &'static str {
+        let var = |name: &str| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        };
+        GlyphSet::detect_from(var).name
+    }
+
+    #[test]
+    fn detects_the_glyph_set() {
+        assert_eq!(detect(&[]), "unicode");
+        assert_eq!(
+            detect(&[("TERM", "xterm-256color"), ("LANG", "en_US.UTF-8")]),
+            "unicode"
+        );
+        assert_eq!(
+            detect(&[("TERM", "linux"), ("LANG", "en_US.UTF-8")]),
+            "ascii"
+        );
+        assert_eq!(detect(&[("TERM", "dumb")]), "ascii");
+        assert_eq!(detect(&[("LANG", "C")]), "ascii");
+        assert_eq!(detect(&[("LANG", "de_DE.utf8")]), "unicode");
+        // the first locale variable set wins, empty ones are skipped
+        assert_eq!(
+            detect(&[("LC_ALL", "POSIX"), ("LANG", "en_US.UTF-8")]),
+            "ascii"
+        );
+        assert_eq!(
+            detect(&[("LC_ALL", ""), ("LC_CTYPE", "C.UTF-8"), ("LANG", "C")]),
+            "unicode"
+        );
+    }
+
+    #[test]
+    fn selects_sets_by_name() {
+        assert_eq!(GlyphSet::by_name("unicode").unwrap().name, "unicode");
+        assert_eq!(GlyphSet::by_name("ascii").unwrap().name, "ascii");
+        assert!(GlyphSet::by_name("auto").is_some());
+        assert!(GlyphSet::by_name("emoji").is_none());
+    }
+
+    #[test]
+    fn ascii_banners_are_text() {
+        assert_eq!(ASCII.title.fit(200), "RiverRaid Rust");
+        assert_eq!(ASCII.crashed.fit(10), "You crashed in the ground.");
+        assert_eq!(UNICODE.title.fit(100), "RiverRaid Rust");
+        assert_ne!(UNICODE.title.fit(101), "RiverRaid Rust");
+    }
+
+    #[test]
+    fn ascii_set_is_ascii() {
+        let banners = [
+            &ASCII.title,
+            &ASCII.good_game,
+            &ASCII.thanks,
+            &ASCII.crashed,
+            &ASCII.killed,
+            &ASCII.no_fuel,
+        ];
+        let sprites = Glyph::ALL
+            .iter()
+            .flat_map(|glyph| ASCII.art(*glyph).rows)
+            .chain(ASCII.player.rows)
+            .copied();
+        let text = sprites
+            .chain(ASCII.pause_box)
+            .chain(banners.map(|banner| banner.fit(u16::MAX)))
+            .collect::<String>();
+        assert!(text.is_ascii(), "{text}");
+        assert!(ASCII.debris.iter().chain(ASCII.gauge).all(char::is_ascii));
+    }
+}
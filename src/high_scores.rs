@@ -0,0 +1,110 @@
+//! The best scores, kept in a small text file between runs.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+const MAX_ENTRIES: usize = 10;
+
+#[derive(Default)]
+pub struct HighScores {
+    path: Option<PathBuf>,
+    scores: Vec<u16>,
+}
+
+impl HighScores {
+    /// `$XDG_DATA_HOME/riverraidrust/highscores`, or the same under `~/.local/share`
+    pub fn default_path() -> Option<PathBuf> {
+        let data_home = env::var_os("XDG_DATA_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))?;
+
+        Some(data_home.join("riverraidrust").join("highscores"))
+    }
+
+    /// Read the scores, a missing or broken file gives an empty table.
+    pub fn load(path: Option<PathBuf>) -> HighScores {
+        let content = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .unwrap_or_default();
+
+        let mut high_scores = HighScores {
+            path,
+            scores: Vec::new(),
+        };
+        for score in content.lines().filter_map(|line| line.trim().parse().ok()) {
+            high_scores.insert(score);
+        }
+
+        high_scores
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content: String = self
+            .scores
+            .iter()
+            .map(|score| format!("{score}\n"))
+            .collect();
+        fs::write(path, content)
+    }
+
+    /// Add a score, returns its rank (from 0) if it made it to the table.
+    pub fn insert(&mut self, score: u16) -> Option<usize> {
+        let rank = self.scores.partition_point(|&other| other >= score);
+        if rank >= MAX_ENTRIES {
+            return None;
+        }
+
+        self.scores.insert(rank, score);
+        self.scores.truncate(MAX_ENTRIES);
+        Some(rank)
+    }
+
+    pub fn scores(&self) -> &[u16] {
+        &self.scores
+    }
+} // end of HighScores implementation.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_best_scores_in_order() {
+        let mut high_scores = HighScores::default();
+        for score in [30, 10, 20] {
+            high_scores.insert(score);
+        }
+        assert_eq!(high_scores.scores(), &[30, 20, 10]);
+
+        for score in 0..20 {
+            high_scores.insert(100 + score);
+        }
+        assert_eq!(high_scores.scores().len(), MAX_ENTRIES);
+        assert_eq!(high_scores.insert(5), None);
+        assert_eq!(high_scores.insert(200), Some(0));
+    }
+
+    #[test]
+    fn survives_a_round_trip() {
+        let path = env::temp_dir().join(format!("riverraid-scores-{}", std::process::id()));
+        let mut high_scores = HighScores::load(Some(path.clone()));
+        high_scores.insert(40);
+        high_scores.insert(70);
+        high_scores.save().unwrap();
+
+        let loaded = HighScores::load(Some(path.clone()));
+        assert_eq!(loaded.scores(), &[70, 40]);
+        fs::remove_file(path).unwrap();
+    }
+}
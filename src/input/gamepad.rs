@@ -0,0 +1,401 @@
+//! Linux joysticks and gamepads, read straight from `/dev/input/event*`.
+//!
+//! The devices are opened non-blocking and read once a frame, the raw
+//! `input_event` records are turned into the same actions as the keyboard.
+//! `/dev/input` is scanned again every few seconds, so a pad plugged in
+//! during a game is picked up and an unplugged one is forgotten.
+
+use std::{
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    io::{self, Read},
+    mem,
+    os::{fd::AsRawFd, unix::fs::OpenOptionsExt},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{KeyCode, KeyEvent};
+
+use super::{Action, Frame};
+
+/// A `struct input_event`: the time, then type, code and value
+const EVENT_SIZE: usize = mem::size_of::<libc::timeval>() + 8;
+
+const EV_KEY: u16 = 0x01;
+const EV_ABS: u16 = 0x03;
+
+const ABS_X: u16 = 0x00;
+const ABS_Y: u16 = 0x01;
+const ABS_HAT0X: u16 = 0x10;
+const ABS_HAT0Y: u16 = 0x11;
+
+const BTN_JOYSTICK: usize = 0x120;
+const BTN_TRIGGER: u16 = 0x120;
+const BTN_THUMB: u16 = 0x121;
+const BTN_GAMEPAD: usize = 0x130;
+const BTN_SOUTH: u16 = 0x130;
+const BTN_WEST: u16 = 0x134;
+const BTN_SELECT: u16 = 0x13a;
+const BTN_START: u16 = 0x13b;
+const BTN_DPAD_UP: u16 = 0x220;
+const BTN_DPAD_DOWN: u16 = 0x221;
+const BTN_DPAD_LEFT: u16 = 0x222;
+const BTN_DPAD_RIGHT: u16 = 0x223;
+
+/// `EVIOCGABS(0)`, add the axis to get its `struct input_absinfo`
+const EVIOCGABS: u32 = 0x8018_4540;
+
+/// How often to look for new pads
+const SCAN_EVERY: Duration = Duration::from_secs(2);
+
+/// How far a stick is pushed before it counts, from 0 to 1
+const DEADZONE: f32 = 0.5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct RawEvent {
+    kind: u16,
+    code: u16,
+    value: i32,
+}
+
+/// Take the whole records out of `pending`, a partial one is left for later.
+fn parse(pending: &mut Vec<u8>) -> Vec<RawEvent> {
+    let whole = pending.len() / EVENT_SIZE * EVENT_SIZE;
+    let time = EVENT_SIZE - 8;
+
+    let events = pending[..whole]
+        .chunks_exact(EVENT_SIZE)
+        .map(|record| RawEvent {
+            kind: u16::from_ne_bytes([record[time], record[time + 1]]),
+            code: u16::from_ne_bytes([record[time + 2], record[time + 3]]),
+            value: i32::from_ne_bytes(record[time + 4..time + 8].try_into().unwrap()),
+        })
+        .collect();
+
+    pending.drain(..whole);
+    events
+}
+
+fn button_action(code: u16) -> Option<Action> {
+    match code {
+        BTN_SOUTH | BTN_WEST | BTN_TRIGGER | BTN_THUMB => Some(Action::Fire),
+        BTN_START => Some(Action::Pause),
+        BTN_SELECT => Some(Action::Quit),
+        BTN_DPAD_UP => Some(Action::Up),
+        BTN_DPAD_DOWN => Some(Action::Down),
+        BTN_DPAD_LEFT => Some(Action::Left),
+        BTN_DPAD_RIGHT => Some(Action::Right),
+        _ => None,
+    }
+}
+
+/// The key a menu sees for a pad action
+fn menu_key(action: Action) -> KeyCode {
+    match action {
+        Action::Up => KeyCode::Up,
+        Action::Down => KeyCode::Down,
+        Action::Left => KeyCode::Left,
+        Action::Right => KeyCode::Right,
+        Action::Fire => KeyCode::Enter,
+        Action::Pause => KeyCode::Char('p'),
+        Action::Quit => KeyCode::Esc,
+    }
+}
+
+/// What is held on one pad.
+struct Pad {
+    /// Range of the stick on each axis
+    ranges: [(i32, i32); 2],
+    stick: (i8, i8),
+    hat: (i8, i8),
+    buttons: HashSet<u16>,
+}
+
+impl Default for Pad {
+    fn default() -> Self {
+        Pad {
+            ranges: [(-32768, 32767); 2],
+            stick: (0, 0),
+            hat: (0, 0),
+            buttons: HashSet::new(),
+        }
+    }
+}
+
+impl Pad {
+    /// -1, 0 or 1 for a stick position
+    fn direction(&self, axis: usize, value: i32) -> i8 {
+        let (min, max) = self.ranges[axis];
+        let center = (min as f32 + max as f32) / 2.0;
+        let ratio = (value as f32 - center) / ((max as f32 - min as f32) / 2.0);
+
+        if ratio < -DEADZONE {
+            -1
+        } else if ratio > DEADZONE {
+            1
+        } else {
+            0
+        }
+    }
+
+    /// Update the state, returns the actions it started.
+    fn apply(&mut self, event: RawEvent) -> Vec<Action> {
+        let before = self.held();
+        match (event.kind, event.code) {
+            // 2 is the auto-repeat of a held button
+            (EV_KEY, code) if event.value != 0 => {
+                self.buttons.insert(code);
+            }
+            (EV_KEY, code) => {
+                self.buttons.remove(&code);
+            }
+            (EV_ABS, ABS_X) => self.stick.0 = self.direction(0, event.value),
+            (EV_ABS, ABS_Y) => self.stick.1 = self.direction(1, event.value),
+            (EV_ABS, ABS_HAT0X) => self.hat.0 = event.value.signum() as i8,
+            (EV_ABS, ABS_HAT0Y) => self.hat.1 = event.value.signum() as i8,
+            _ => {}
+        }
+
+        self.held().difference(&before).copied().collect()
+    }
+
+    fn held(&self) -> HashSet<Action> {
+        let mut held: HashSet<Action> = self
+            .buttons
+            .iter()
+            .filter_map(|&code| button_action(code))
+            .collect();
+        for (c, l) in [self.stick, self.hat] {
+            match c {
+                -1 => held.insert(Action::Left),
+                1 => held.insert(Action::Right),
+                _ => false,
+            };
+            match l {
+                -1 => held.insert(Action::Up),
+                1 => held.insert(Action::Down),
+                _ => false,
+            };
+        }
+        held
+    }
+} // end of Pad implementation.
+
+struct Device {
+    path: PathBuf,
+    source: Box<dyn Read>,
+    pad: Pad,
+    pending: Vec<u8>,
+}
+
+impl Device {
+    fn open(path: PathBuf) -> io::Result<Device> {
+        let file = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&path)?;
+
+        let mut pad = Pad::default();
+        for (axis, code) in [ABS_X, ABS_Y].into_iter().enumerate() {
+            if let Some(range) = abs_range(&file, code) {
+                pad.ranges[axis] = range;
+            }
+        }
+
+        Ok(Device {
+            path,
+            source: Box::new(file),
+            pad,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Read what the pad did since the last frame, fails once unplugged.
+    fn read(&mut self, frame: &mut Frame) -> io::Result<()> {
+        let mut buffer = [0; EVENT_SIZE * 64];
+        loop {
+            match self.source.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(count) => self.pending.extend_from_slice(&buffer[..count]),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) => return Err(err),
+            }
+        }
+
+        for event in parse(&mut self.pending) {
+            for action in self.pad.apply(event) {
+                frame.keys.push(KeyEvent::from(menu_key(action)));
+                frame.pressed.insert(action);
+                frame.held.insert(action);
+            }
+        }
+        frame.held.extend(self.pad.held());
+        Ok(())
+    }
+} // end of Device implementation.
+
+/// The `minimum` and `maximum` of an axis.
+fn abs_range(file: &File, axis: u16) -> Option<(i32, i32)> {
+    // value, minimum, maximum, fuzz, flat, resolution
+    let mut info = [0i32; 6];
+    // SAFETY: EVIOCGABS writes one `struct input_absinfo`, six i32, in `info`
+    let result = unsafe {
+        libc::ioctl(
+            file.as_raw_fd(),
+            (EVIOCGABS + axis as u32) as _,
+            info.as_mut_ptr(),
+        )
+    };
+
+    (result >= 0 && info[1] < info[2]).then_some((info[1], info[2]))
+}
+
+/// Check a bit of a sysfs bitmap: hex words, the most significant first.
+fn has_bit(bitmap: &str, bit: usize) -> bool {
+    let word_bits = usize::BITS as usize;
+    bitmap
+        .split_whitespace()
+        .rev()
+        .nth(bit / word_bits)
+        .and_then(|word| usize::from_str_radix(word, 16).ok())
+        .is_some_and(|word| word & (1 << (bit % word_bits)) != 0)
+}
+
+/// Joysticks and pads have joystick or gamepad buttons, keyboards don't.
+fn is_pad(name: &str) -> bool {
+    fs::read_to_string(format!("/sys/class/input/{name}/device/capabilities/key"))
+        .is_ok_and(|keys| has_bit(&keys, BTN_JOYSTICK) || has_bit(&keys, BTN_GAMEPAD))
+}
+
+#[derive(Default)]
+pub struct Gamepads {
+    devices: Vec<Device>,
+    last_scan: Option<Instant>,
+}
+
+impl Gamepads {
+    /// Add the pads to the frame, looking for new ones once in a while.
+    pub fn update(&mut self, frame: &mut Frame) {
+        if self.last_scan.is_none_or(|at| at.elapsed() >= SCAN_EVERY) {
+            self.scan();
+        }
+
+        self.devices.retain_mut(|device| device.read(frame).is_ok());
+    }
+
+    fn scan(&mut self) {
+        self.last_scan = Some(Instant::now());
+        let Ok(entries) = fs::read_dir("/dev/input") else {
+            return;
+        };
+
+        for path in entries.flatten().map(|entry| entry.path()) {
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if !name.starts_with("event") || self.devices.iter().any(|device| device.path == path) {
+                continue;
+            }
+
+            // most event devices are only readable by the `input` group
+            if is_pad(name) {
+                if let Ok(device) = Device::open(path) {
+                    self.devices.push(device);
+                }
+            }
+        }
+    }
+} // end of Gamepads implementation.
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn record(kind: u16, code: u16, value: i32) -> Vec<u8> {
+        let mut record = vec![0; EVENT_SIZE - 8];
+        record.extend(kind.to_ne_bytes());
+        record.extend(code.to_ne_bytes());
+        record.extend(value.to_ne_bytes());
+        record
+    }
+
+    fn device(stream: Vec<u8>) -> Device {
+        Device {
+            path: PathBuf::from("fake"),
+            source: Box::new(Cursor::new(stream)),
+            pad: Pad {
+                ranges: [(0, 255); 2],
+                ..Default::default()
+            },
+            pending: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn parses_split_records() {
+        let mut pending = record(EV_KEY, BTN_SOUTH, 1);
+        pending.extend(record(EV_ABS, ABS_X, -200));
+        pending.truncate(EVENT_SIZE + 3);
+
+        let events = parse(&mut pending);
+        let press = RawEvent {
+            kind: EV_KEY,
+            code: BTN_SOUTH,
+            value: 1,
+        };
+        assert_eq!(events, [press]);
+        assert_eq!(pending.len(), 3);
+    }
+
+    #[test]
+    fn stick_and_buttons_become_actions() {
+        let stream = [
+            record(EV_ABS, ABS_X, 10),
+            record(EV_ABS, ABS_Y, 130),
+            record(EV_KEY, BTN_SOUTH, 1),
+        ]
+        .concat();
+        let mut device = device(stream);
+
+        let mut frame = Frame::default();
+        device.read(&mut frame).unwrap();
+        assert!(frame.pressed(Action::Left) && frame.pressed(Action::Fire));
+        assert!(!frame.held(Action::Up) && !frame.held(Action::Down));
+        assert_eq!(frame.keys[1], KeyEvent::from(KeyCode::Enter));
+
+        // nothing new, the stick is still pushed
+        let mut frame = Frame::default();
+        device.read(&mut frame).unwrap();
+        assert!(frame.held(Action::Left) && !frame.pressed(Action::Left));
+        assert!(frame.keys.is_empty());
+    }
+
+    #[test]
+    fn unplugged_pads_are_forgotten() {
+        struct Unplugged;
+        impl Read for Unplugged {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::from_raw_os_error(libc::ENODEV))
+            }
+        }
+
+        let mut gamepads = Gamepads {
+            devices: vec![device(Vec::new())],
+            last_scan: Some(Instant::now()),
+        };
+        gamepads.devices[0].source = Box::new(Unplugged);
+        gamepads.update(&mut Frame::default());
+        assert!(gamepads.devices.is_empty());
+    }
+
+    #[test]
+    fn reads_sysfs_bitmaps() {
+        let keys = "7fdb000000000000 0 0 0 0";
+        assert!(has_bit(keys, BTN_GAMEPAD));
+        assert!(!has_bit(keys, BTN_JOYSTICK));
+        assert!(!has_bit("0", BTN_GAMEPAD));
+    }
+}
@@ -0,0 +1,373 @@
+//! Everything the keyboard and the mouse did during a frame.
+//!
+//! All the events read while waiting for the next frame are gathered in a
+//! [`Frame`], so a fire pressed together with a move is never lost.
+//!
+//! Terminals supporting the kitty keyboard protocol report releases too, the
+//! keys are then held until released. Elsewhere a key only counts for the
+//! frame it was pressed (or auto-repeated) in.
+//!
+//! With the mouse captured, the left button fires until released and the
+//! plane steers toward the column of the pointer, until a steering key is
+//! pressed.
+//!
+//! Raw mode turns Ctrl-C, Ctrl-Z and Ctrl-L into keys like the others, the
+//! frame tells them apart, like the terminal losing the focus or resizing.
+//!
+//! With the `gamepad` feature, Linux joysticks and pads are read next to the
+//! keyboard and give the same actions.
+
+use std::{collections::HashSet, io, mem, time::Instant};
+
+use crossterm::event::{
+    poll, read, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent,
+    MouseEventKind,
+};
+
+#[cfg(feature = "gamepad")]
+mod gamepad;
+
+/// What a key means in the game.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Fire,
+    Pause,
+    Quit,
+}
+
+impl Action {
+    pub fn from_key(code: KeyCode) -> Option<Action> {
+        match code {
+            KeyCode::Char('w') | KeyCode::Up => Some(Action::Up),
+            KeyCode::Char('s') | KeyCode::Down => Some(Action::Down),
+            KeyCode::Char('a') | KeyCode::Left => Some(Action::Left),
+            KeyCode::Char('d') | KeyCode::Right => Some(Action::Right),
+            KeyCode::Char(' ') => Some(Action::Fire),
+            KeyCode::Char('p') => Some(Action::Pause),
+            KeyCode::Char('q') => Some(Action::Quit),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct Frame {
+    /// The keys pressed or repeated, in order, for the menus
+    pub keys: Vec<KeyEvent>,
+    /// The clicks, moves and wheel turns, in order
+    pub mouse: Vec<MouseEvent>,
+    /// The column the plane steers toward
+    pub pointer: Option<u16>,
+    /// Ctrl-C was pressed, the SIGINT raw mode holds back
+    pub interrupt: bool,
+    /// Ctrl-Z was pressed
+    pub suspend: bool,
+    /// Ctrl-L was pressed or the terminal resized, the screen may be garbled
+    pub redraw: bool,
+    /// The terminal went to the background
+    pub focus_lost: bool,
+    pressed: HashSet<Action>,
+    held: HashSet<Action>,
+}
+
+impl Frame {
+    /// The action was started during this frame
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    /// The action is going on, it may have been started long ago
+    pub fn held(&self, action: Action) -> bool {
+        self.held.contains(&action)
+    }
+}
+
+#[derive(Default)]
+pub struct Input {
+    /// The terminal reports key releases
+    enhanced: bool,
+    /// Keys down and not released yet, only known when `enhanced`
+    held: HashSet<KeyCode>,
+    /// The left button is down
+    clicking: bool,
+    /// Where the mouse last went, forgotten on a steering key
+    pointer: Option<u16>,
+    frame: Frame,
+    #[cfg(feature = "gamepad")]
+    gamepads: gamepad::Gamepads,
+}
+
+impl Input {
+    pub fn new(enhanced: bool) -> Input {
+        Input {
+            enhanced,
+            ..Default::default()
+        }
+    }
+
+    /// Sleep on the input until `deadline`, returns what happened meanwhile.
+    pub fn next_frame(&mut self, deadline: Instant) -> io::Result<Frame> {
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            if !poll(timeout)? {
+                #[cfg(feature = "gamepad")]
+                self.gamepads.update(&mut self.frame);
+                return Ok(self.end_frame());
+            }
+            self.handle(read()?);
+        }
+    }
+
+    pub fn handle(&mut self, event: Event) {
+        let key = match event {
+            Event::Key(key) => key,
+            Event::Mouse(mouse) => return self.handle_mouse(mouse),
+            Event::FocusLost => {
+                // the releases go to the window with the focus
+                self.release_all();
+                return self.frame.focus_lost = true;
+            }
+            Event::Resize(..) => return self.frame.redraw = true,
+            _ => return,
+        };
+        let action = Action::from_key(key.code);
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            let pressed = key.kind == KeyEventKind::Press;
+            match key.code {
+                KeyCode::Char('c') => return self.frame.interrupt |= pressed,
+                KeyCode::Char('z') => {
+                    // nor are they seen while stopped
+                    self.release_all();
+                    return self.frame.suspend |= pressed;
+                }
+                KeyCode::Char('l') => return self.frame.redraw |= pressed,
+                _ => {}
+            }
+        }
+
+        match key.kind {
+            KeyEventKind::Press => {
+                self.frame.keys.push(key);
+                if let Some(Action::Left | Action::Right) = action {
+                    self.pointer = None;
+                }
+                if let Some(action) = action {
+                    self.frame.pressed.insert(action);
+                    self.frame.held.insert(action);
+                }
+                if self.enhanced {
+                    self.held.insert(key.code);
+                }
+            }
+            KeyEventKind::Repeat => {
+                self.frame.keys.push(key);
+                if let Some(action) = action {
+                    self.frame.held.insert(action);
+                }
+            }
+            KeyEventKind::Release => {
+                self.held.remove(&key.code);
+            }
+        }
+    }
+
+    /// Forget the keys and the button down, their releases will not come
+    fn release_all(&mut self) {
+        self.held.clear();
+        self.clicking = false;
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        self.frame.mouse.push(mouse);
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.frame.pressed.insert(Action::Fire);
+                self.frame.held.insert(Action::Fire);
+                self.clicking = true;
+            }
+            MouseEventKind::Up(MouseButton::Left) => self.clicking = false,
+            _ => {}
+        }
+        if let MouseEventKind::Moved | MouseEventKind::Drag(_) | MouseEventKind::Down(_) =
+            mouse.kind
+        {
+            self.pointer = Some(mouse.column);
+        }
+    }
+
+    /// The frame so far, the next one starts empty but for the held keys.
+    pub fn end_frame(&mut self) -> Frame {
+        let mut frame = mem::take(&mut self.frame);
+        let held = self.held.iter().filter_map(|&code| Action::from_key(code));
+        frame.held.extend(held);
+        if self.clicking {
+            frame.held.insert(Action::Fire);
+        }
+        frame.pointer = self.pointer;
+        frame
+    }
+} // end of Input implementation.
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyEventState, KeyModifiers};
+
+    use super::*;
+
+    fn key(code: KeyCode, kind: KeyEventKind) -> Event {
+        Event::Key(KeyEvent {
+            code,
+            modifiers: KeyModifiers::NONE,
+            kind,
+            state: KeyEventState::NONE,
+        })
+    }
+
+    #[test]
+    fn keeps_every_key_of_a_frame() {
+        let mut input = Input::default();
+        input.handle(key(KeyCode::Left, KeyEventKind::Press));
+        input.handle(key(KeyCode::Char(' '), KeyEventKind::Press));
+        input.handle(key(KeyCode::Char('x'), KeyEventKind::Press));
+
+        let frame = input.end_frame();
+        assert_eq!(frame.keys.len(), 3);
+        assert!(frame.pressed(Action::Left) && frame.held(Action::Left));
+        assert!(frame.pressed(Action::Fire));
+
+        // without releases, nothing is held in the next frame
+        let frame = input.end_frame();
+        assert!(frame.keys.is_empty() && !frame.held(Action::Left));
+    }
+
+    #[test]
+    fn holds_keys_until_released() {
+        let mut input = Input::new(true);
+        input.handle(key(KeyCode::Up, KeyEventKind::Press));
+        input.handle(key(KeyCode::Left, KeyEventKind::Press));
+        input.end_frame();
+
+        let frame = input.end_frame();
+        assert!(frame.held(Action::Up) && frame.held(Action::Left));
+        assert!(!frame.pressed(Action::Up));
+
+        input.handle(key(KeyCode::Up, KeyEventKind::Release));
+        let frame = input.end_frame();
+        assert!(!frame.held(Action::Up) && frame.held(Action::Left));
+    }
+
+    #[test]
+    fn a_tap_inside_a_frame_still_counts() {
+        let mut input = Input::new(true);
+        input.handle(key(KeyCode::Char(' '), KeyEventKind::Press));
+        input.handle(key(KeyCode::Char(' '), KeyEventKind::Release));
+
+        let frame = input.end_frame();
+        assert!(frame.pressed(Action::Fire) && frame.held(Action::Fire));
+        assert!(!input.end_frame().held(Action::Fire));
+    }
+
+    #[test]
+    fn the_mouse_fires_and_points() {
+        let mouse = |kind, column| {
+            Event::Mouse(MouseEvent {
+                kind,
+                column,
+                row: 3,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+        let mut input = Input::default();
+        input.handle(mouse(MouseEventKind::Moved, 12));
+        input.handle(mouse(MouseEventKind::Down(MouseButton::Left), 14));
+        let frame = input.end_frame();
+        assert!(frame.pressed(Action::Fire) && frame.held(Action::Fire));
+        assert_eq!((frame.mouse.len(), frame.pointer), (2, Some(14)));
+
+        // held until released, the pointer stays
+        let frame = input.end_frame();
+        assert!(frame.held(Action::Fire) && !frame.pressed(Action::Fire));
+        input.handle(mouse(MouseEventKind::Up(MouseButton::Left), 14));
+        let frame = input.end_frame();
+        assert!(!frame.held(Action::Fire) && frame.pointer == Some(14));
+
+        // until the keys steer
+        input.handle(key(KeyCode::Left, KeyEventKind::Press));
+        assert_eq!(input.end_frame().pointer, None);
+    }
+
+    #[test]
+    fn ctrl_z_and_focus_are_not_keys() {
+        let mut input = Input::default();
+        input.handle(Event::Key(KeyEvent::new(
+            KeyCode::Char('z'),
+            KeyModifiers::CONTROL,
+        )));
+        input.handle(Event::FocusLost);
+        let frame = input.end_frame();
+        assert!(frame.suspend && frame.focus_lost && frame.keys.is_empty());
+        assert!(!input.end_frame().suspend);
+    }
+
+    #[test]
+    fn ctrl_c_interrupts() {
+        let mut input = Input::default();
+        input.handle(Event::Key(KeyEvent::new(
+            KeyCode::Char('c'),
+            KeyModifiers::CONTROL,
+        )));
+        let frame = input.end_frame();
+        assert!(frame.interrupt && frame.keys.is_empty());
+        assert!(!input.end_frame().interrupt);
+
+        // a plain c is only a key
+        input.handle(Event::Key(KeyEvent::from(KeyCode::Char('c'))));
+        let frame = input.end_frame();
+        assert!(!frame.interrupt && frame.keys.len() == 1);
+    }
+
+    #[test]
+    fn ctrl_l_and_resizes_ask_for_a_redraw() {
+        let mut input = Input::new(true);
+        let ctrl_l = |kind| {
+            Event::Key(KeyEvent {
+                modifiers: KeyModifiers::CONTROL,
+                ..KeyEvent::new_with_kind(KeyCode::Char('l'), KeyModifiers::CONTROL, kind)
+            })
+        };
+        input.handle(ctrl_l(KeyEventKind::Press));
+        let frame = input.end_frame();
+        assert!(frame.redraw && frame.keys.is_empty());
+        input.handle(ctrl_l(KeyEventKind::Release));
+        assert!(!input.end_frame().redraw);
+
+        input.handle(Event::Resize(80, 24));
+        assert!(input.end_frame().redraw);
+        assert!(!input.end_frame().redraw);
+    }
+
+    #[test]
+    fn losing_the_focus_lets_go_of_everything() {
+        let mut input = Input::new(true);
+        input.handle(key(KeyCode::Left, KeyEventKind::Press));
+        input.handle(Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 12,
+            row: 3,
+            modifiers: KeyModifiers::NONE,
+        }));
+        input.end_frame();
+        let frame = input.end_frame();
+        assert!(frame.held(Action::Left) && frame.held(Action::Fire));
+
+        // the releases happen in another window
+        input.handle(Event::FocusLost);
+        input.end_frame();
+        let frame = input.end_frame();
+        assert!(!frame.held(Action::Left) && !frame.held(Action::Fire));
+    }
+}
@@ -0,0 +1,433 @@
+//! Level files: hand-authored stretches of river.
+//!
+//! A level is a list of sections, each giving the width of the river and
+//! how far its middle is from the middle of the screen once the section is
+//! over; the banks go there a little every row. Kinds of the content pack
+//! are placed on the rows of the level. At the end the level starts over,
+//! or the random river takes over from where it left the banks.
+//!
+//! The level editor of `--edit` changes levels with the methods here.
+
+use std::{fs, io, ops::Range, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    content::{Content, BULLET},
+    world::store::Collider,
+};
+
+/// Columns of water the plane needs to get through
+pub const PASSAGE: u16 = 3;
+
+/// What comes after the last row of a level.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LevelEnd {
+    Loop,
+    #[default]
+    Procedural,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Section {
+    /// Rows it lasts
+    pub length: u32,
+    /// Columns of water
+    pub width: u16,
+    /// Columns from the middle of the screen to the middle of the river
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub offset: i16,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Place {
+    /// Row of the level, from 0
+    pub at: u32,
+    /// A kind of the content pack
+    pub kind: String,
+    /// Columns from the middle of the river
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub column: i16,
+    /// From bank to bank, like a bridge
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub span: bool,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Level {
+    #[serde(default)]
+    pub end: LevelEnd,
+    #[serde(rename = "section")]
+    pub sections: Vec<Section>,
+    #[serde(rename = "place", default)]
+    pub places: Vec<Place>,
+}
+
+impl Level {
+    /// A straight stretch of river, to start a new level from
+    pub fn blank() -> Level {
+        Level {
+            end: LevelEnd::Procedural,
+            sections: vec![Section {
+                length: 100,
+                width: 20,
+                offset: 0,
+            }],
+            places: vec![],
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>, content: &Content) -> io::Result<Level> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|err| {
+            io::Error::new(
+                err.kind(),
+                format!("cannot read level {}: {err}", path.display()),
+            )
+        })?;
+        Level::from_toml(&text, content).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid level {}: {err}", path.display()),
+            )
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let text = toml::to_string(self)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+        fs::write(path, text)
+    }
+
+    pub fn from_toml(text: &str, content: &Content) -> Result<Level, String> {
+        let level: Level = toml::from_str(text).map_err(|err| err.to_string())?;
+        level.validate(content)?;
+        Ok(level)
+    }
+
+    /// Check the level makes sense with the kinds of `content`.
+    pub fn validate(&self, content: &Content) -> Result<(), String> {
+        if self.sections.is_empty() {
+            return Err("a level needs a [[section]] at least".into());
+        }
+        for (number, section) in self.sections.iter().enumerate() {
+            if section.length == 0 {
+                return Err(format!("section {}: `length` must be above 0", number + 1));
+            }
+            if section.width < PASSAGE {
+                return Err(format!(
+                    "section {}: `width` must be {PASSAGE} at least, for the plane to get through",
+                    number + 1
+                ));
+            }
+        }
+
+        let length = self.length();
+        for (number, place) in self.places.iter().enumerate() {
+            if place.kind == BULLET || content.kind(&place.kind).is_none() {
+                return Err(format!(
+                    "place {}: unknown kind `{}`",
+                    number + 1,
+                    place.kind
+                ));
+            }
+            if place.at >= length {
+                return Err(format!(
+                    "place {}: `at` is past the end of the level, {length} rows long",
+                    number + 1
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Rows in the level
+    pub fn length(&self) -> u32 {
+        self.sections.iter().map(|section| section.length).sum()
+    }
+
+    /// The banks on `row` of the level, on a screen `maxc` columns wide.
+    pub fn banks(&self, row: u32, maxc: u16) -> (u16, u16) {
+        // where the section before left the river, the first one starts there
+        let mut from = &self.sections[0];
+        let mut start = 0;
+        let mut width = from.width as f32;
+        let mut offset = from.offset as f32;
+        for section in self.sections.iter() {
+            if row < start + section.length {
+                let done = (row - start + 1) as f32 / section.length as f32;
+                width = from.width as f32 + (section.width as f32 - from.width as f32) * done;
+                offset = from.offset as f32 + (section.offset as f32 - from.offset as f32) * done;
+                break;
+            }
+            start += section.length;
+            from = section;
+            (width, offset) = (section.width as f32, section.offset as f32);
+        }
+
+        let maxc = maxc as i32;
+        let width = (width.round() as i32).clamp(PASSAGE as i32, maxc - 2);
+        let left = (maxc / 2 + offset.round() as i32 - width / 2).clamp(1, maxc - 1 - width);
+        (left as u16, (left + width) as u16)
+    }
+
+    /// The screen columns `place` takes on its row
+    pub fn columns(&self, place: &Place, maxc: u16) -> Range<u16> {
+        let (left, right) = self.banks(place.at, maxc);
+        if place.span {
+            return left..right;
+        }
+        let column = ((left + right) / 2) as i32 + place.column as i32;
+        let column = column.clamp(left as i32, right as i32 - 1) as u16;
+        column..column + 1
+    }
+
+    /// The section `row` is in, with the row it starts on
+    fn section_at(&self, row: u32) -> Option<(usize, u32)> {
+        let mut start = 0;
+        for (index, section) in self.sections.iter().enumerate() {
+            if row < start + section.length {
+                return Some((index, start));
+            }
+            start += section.length;
+        }
+        None
+    }
+
+    /// Make the level `row + 1` rows long at least, the last section goes on
+    fn reach(&mut self, row: u32) {
+        let length = self.length();
+        if row >= length {
+            let last = self.sections.len() - 1;
+            self.sections[last].length += row + 1 - length;
+        }
+    }
+
+    /// Move the banks of `row` to the screen columns `left` and `right`.
+    ///
+    /// The section of the row ends there and a new one takes the rest of it
+    /// to where the section went; past the end, a new section gets there.
+    pub fn set_banks(
+        &mut self,
+        row: u32,
+        (left, right): (u16, u16),
+        maxc: u16,
+    ) -> Result<(), String> {
+        if right < left + PASSAGE {
+            return Err(format!("the river must be {PASSAGE} columns wide at least"));
+        }
+        let width = right - left;
+        let offset = (left + width / 2) as i16 - (maxc / 2) as i16;
+
+        let Some((index, start)) = self.section_at(row) else {
+            let length = row + 1 - self.length();
+            self.sections.push(Section {
+                length,
+                width,
+                offset,
+            });
+            return Ok(());
+        };
+        let section = &mut self.sections[index];
+        let length = row + 1 - start;
+        if length < section.length {
+            let rest = Section {
+                length: section.length - length,
+                ..section.clone()
+            };
+            section.length = length;
+            self.sections.insert(index + 1, rest);
+        }
+        let section = &mut self.sections[index];
+        (section.width, section.offset) = (width, offset);
+        Ok(())
+    }
+
+    /// The places on `row` taking the screen column `column`
+    fn places_at(&self, row: u32, column: u16, maxc: u16) -> impl Iterator<Item = usize> + '_ {
+        (0..self.places.len()).filter(move |&index| {
+            let place = &self.places[index];
+            place.at == row && self.columns(place, maxc).contains(&column)
+        })
+    }
+
+    /// Put `kind` on `row`, at the screen column `column` or from bank to
+    /// bank; it replaces what was there.
+    pub fn place(&mut self, row: u32, column: u16, kind: &str, span: bool, maxc: u16) {
+        self.reach(row);
+        self.remove(row, column, maxc);
+        let (left, right) = self.banks(row, maxc);
+        let column = if span {
+            0
+        } else {
+            column.clamp(left, right - 1) as i16 - ((left + right) / 2) as i16
+        };
+        self.places.push(Place {
+            at: row,
+            kind: kind.into(),
+            column,
+            span,
+        });
+    }
+
+    /// Take away what is at the screen column `column` of `row`, returns
+    /// whether there was something.
+    pub fn remove(&mut self, row: u32, column: u16, maxc: u16) -> bool {
+        let doomed: Vec<usize> = self.places_at(row, column, maxc).collect();
+        for &index in doomed.iter().rev() {
+            self.places.remove(index);
+        }
+        !doomed.is_empty()
+    }
+
+    /// The rows the plane cannot get through: the river moves too far from
+    /// one row to the next, or a wall that cannot be shot down spans it. A
+    /// looping level goes on from its last row to its first.
+    pub fn impassable(&self, content: &Content, maxc: u16) -> Vec<u32> {
+        let wall = |place: &Place| {
+            place.span
+                && content.kind(&place.kind).is_some_and(|kind| {
+                    kind.collider == Some(Collider::Hostile) && kind.health.is_none()
+                })
+        };
+        let next = |row: u32| match self.end {
+            LevelEnd::Loop if row + 1 == self.length() => 0,
+            _ => row + 1,
+        };
+        (0..self.length())
+            .filter(|&row| {
+                let (left, right) = self.banks(row, maxc);
+                let (next_left, next_right) = self.banks(next(row), maxc);
+                right.min(next_right) < left.max(next_left) + PASSAGE
+                    || self
+                        .places
+                        .iter()
+                        .any(|place| place.at == row && wall(place))
+            })
+            .collect()
+    }
+} // end of Level implementation.
+
+fn is_zero(value: &i16) -> bool {
+    *value == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CANYON: &str = r#"
+        end = "loop"
+
+        [[section]]
+        length = 10
+        width = 20
+
+        [[section]]
+        length = 4
+        width = 8
+        offset = -6
+
+        [[place]]
+        at = 3
+        kind = "enemy"
+        column = 2
+
+        [[place]]
+        at = 12
+        kind = "bridge"
+        span = true
+    "#;
+
+    #[test]
+    fn banks_go_from_section_to_section() {
+        let level = Level::from_toml(CANYON, Content::built_in()).unwrap();
+        assert_eq!(level.length(), 14);
+        assert_eq!(level.end, LevelEnd::Loop);
+
+        assert_eq!(level.banks(0, 80), (30, 50));
+        assert_eq!(level.banks(9, 80), (30, 50));
+        // halfway narrower and to the left
+        assert_eq!(level.banks(11, 80), (30, 44));
+        assert_eq!(level.banks(13, 80), (30, 38));
+        // past the end, where the last section left it
+        assert_eq!(level.banks(20, 80), (30, 38));
+        // never off a narrow screen
+        assert_eq!(level.banks(0, 16), (1, 15));
+    }
+
+    #[test]
+    fn levels_are_checked_against_the_pack() {
+        fn error(text: &str) -> String {
+            Level::from_toml(text, Content::built_in()).unwrap_err()
+        }
+        assert_eq!(
+            error(&CANYON.replace("\"enemy\"", "\"dragon\"")),
+            "place 1: unknown kind `dragon`"
+        );
+        assert_eq!(
+            error(&CANYON.replace("at = 12", "at = 14")),
+            "place 2: `at` is past the end of the level, 14 rows long"
+        );
+        assert!(error(&CANYON.replace("width = 8", "width = 2")).starts_with("section 2:"));
+        assert!(error("end = \"loop\"").contains("section"));
+    }
+
+    #[test]
+    fn painting_banks_splits_the_section() {
+        let mut level = Level::from_toml(CANYON, Content::built_in()).unwrap();
+        level.set_banks(4, (10, 20), 80).unwrap();
+        assert_eq!(level.banks(4, 80), (10, 20));
+        assert_eq!(level.banks(9, 80), (30, 50));
+        assert_eq!(level.sections.len(), 3);
+        assert_eq!(level.length(), 14);
+
+        // past the end it grows
+        level.set_banks(19, (40, 44), 80).unwrap();
+        assert_eq!((level.length(), level.banks(19, 80)), (20, (40, 44)));
+        assert!(level.set_banks(2, (10, 11), 80).is_err());
+
+        // what is placed stays in the river and round trips
+        level.place(5, 0, "depot", false, 80);
+        level.place(5, 40, "bridge", true, 80);
+        assert_eq!(level.places.len(), 4);
+        let text = toml::to_string(&level).unwrap();
+        assert_eq!(
+            Level::from_toml(&text, Content::built_in()),
+            Ok(level.clone())
+        );
+        assert_eq!(level.columns(&level.places[2], 80), 14..15);
+        assert!(level.remove(5, 20, 80));
+        assert!(!level.remove(5, 20, 80));
+        assert_eq!(level.places.len(), 3);
+    }
+
+    #[test]
+    fn walls_and_jumps_are_impassable() {
+        let wall = "[[kind]]\nname = \"wall\"\nart = \"bridge\"\ncolor = \"enemy\"\nhostile = true";
+        let content = Content::from_toml(&[("wall.toml".into(), wall.into())]).unwrap();
+        let mut level = Level::from_toml(CANYON, &content).unwrap();
+        assert!(level.impassable(&content, 80).is_empty());
+
+        level.set_banks(11, (60, 70), 80).unwrap();
+        level.place(2, 40, "wall", true, 80);
+        // the bridge can be shot down, the wall cannot
+        assert_eq!(level.impassable(&content, 80), [2, 10, 11, 12]);
+    }
+
+    #[test]
+    fn looping_levels_jump_back_to_the_start() {
+        let content = Content::built_in();
+        let mut level = Level::from_toml(CANYON, content).unwrap();
+        level.set_banks(13, (60, 70), 80).unwrap();
+        // the river bends there within reach, then jumps back to the start
+        assert_eq!(level.impassable(content, 80), [13]);
+
+        level.end = LevelEnd::Procedural;
+        assert!(level.impassable(content, 80).is_empty());
+    }
+}
@@ -1,47 +1,47 @@
-use std::io::stdout;
-use stout_ext::StdoutExt;
-
-use crossterm::{
-    cursor::{Hide, Show},
-    terminal::{disable_raw_mode, enable_raw_mode, size},
-    ExecutableCommand,
-};
+use crossterm::terminal::size;
 
+mod app;
 mod canvas;
+mod content;
 mod drawable;
 mod entities;
 mod events;
+mod glyphs;
+mod high_scores;
+mod input;
+mod level;
+mod options;
+mod sound;
+mod sprite;
 mod stout_ext;
+mod terminal;
+mod theme;
 mod world;
 
-use events::*;
+use app::App;
+use high_scores::HighScores;
+use input::Input;
+use options::Options;
+use terminal::Terminal;
 use world::*;
 
 fn main() -> std::io::Result<()> {
-    // init the screen
-    let mut sc = stdout();
-    let (maxc, maxl) = size().unwrap();
-    sc.execute(Hide)?;
-    enable_raw_mode()?;
-
-    // init the world
-    let slowness = 60;
-    let mut world = World::new(maxc, maxl);
-
-    // show welcoming banner
-    world.welcome_screen(&mut sc)?;
-
-    // Main game loop
-    // - Events
-    // - Physics
-    // - Drawing
-    world.game_loop(&mut sc, slowness)?;
-
-    // game is finished
-    world.clear_screen(&mut sc)?;
-    world.goodbye_screen(&mut sc)?;
-
-    sc.clear_all()?.execute(Show)?;
-    disable_raw_mode()?;
-    Ok(())
+    let options = match Options::from_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(2);
+        }
+    };
+
+    let (maxc, maxl) = size()?;
+    // given back when this goes out of scope, or on a panic; the level
+    // editor paints with the mouse, and games play with it if asked
+    let mut terminal = Terminal::enter(options.mouse || options.edit.is_some())?;
+
+    // Menus, game and scores, until the player quits
+    let high_scores = HighScores::load(HighScores::default_path());
+    let mut app =
+        App::new(maxc, maxl, options, high_scores).with_input(Input::new(terminal.enhanced()));
+    app.run(&mut terminal)
 }
@@ -0,0 +1,296 @@
+//! Command line options, also changed from the options screen.
+
+use std::{
+    env, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    content::Content,
+    glyphs::{GlyphSet, ASCII, UNICODE},
+    level::Level,
+    sound::{BellBackend, SoundBus},
+    theme::{ColorSupport, Theme},
+    world::{Controls, FuelWarning, Movement},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SoundChoice {
+    Bell,
+    #[cfg(feature = "audio")]
+    Tones,
+    Off,
+}
+
+impl SoundChoice {
+    const ALL: &'static [SoundChoice] = &[
+        SoundChoice::Bell,
+        #[cfg(feature = "audio")]
+        SoundChoice::Tones,
+        SoundChoice::Off,
+    ];
+
+    pub fn parse(name: &str) -> Option<SoundChoice> {
+        SoundChoice::ALL
+            .iter()
+            .copied()
+            .find(|choice| choice.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SoundChoice::Bell => "bell",
+            #[cfg(feature = "audio")]
+            SoundChoice::Tones => "tones",
+            SoundChoice::Off => "off",
+        }
+    }
+
+    /// A new bus for a game
+    pub fn bus(self) -> SoundBus {
+        match self {
+            SoundChoice::Bell => SoundBus::new(Box::new(BellBackend)),
+            #[cfg(feature = "audio")]
+            SoundChoice::Tones => SoundBus::new(Box::new(crate::sound::ToneBackend::new())),
+            SoundChoice::Off => SoundBus::muted(),
+        }
+    }
+}
+
+pub struct Options {
+    pub theme: Theme,
+    pub glyphs: &'static GlyphSet,
+    pub sound: SoundChoice,
+    pub movement: Movement,
+    pub controls: Controls,
+    pub fuel_warning: FuelWarning,
+    /// What floats down the river
+    pub content: Content,
+    /// Played instead of the random river, when given
+    pub level: Option<Level>,
+    /// The level file of the level editor, `level` is what it holds
+    pub edit: Option<PathBuf>,
+    /// Capture the mouse, to steer, fire and pick in the menus with it
+    pub mouse: bool,
+    /// Time between full redraws, for terminals losing cells on the way
+    pub redraw_every: Option<Duration>,
+}
+
+impl Options {
+    pub fn from_args() -> io::Result<Options> {
+        Options::parse(env::args().skip(1))
+    }
+
+    pub fn parse(args: impl IntoIterator<Item = String>) -> io::Result<Options> {
+        let mut theme = Theme::default();
+        let mut color_support = ColorSupport::detect();
+        let mut glyphs = GlyphSet::detect();
+        let mut sound = SoundChoice::Bell;
+        let mut movement = Movement::default();
+        let mut controls = Controls::Free;
+        let mut fuel_warning = FuelWarning::default();
+        let mut content = None;
+        let mut level = None;
+        let mut edit = None;
+        let mut mouse = false;
+        let mut redraw_every = None;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--theme" => {
+                    let name = value_of(&arg, args.next())?;
+                    theme = Theme::load(&name)?;
+                }
+                "--colors" => {
+                    let name = value_of(&arg, args.next())?;
+                    color_support = ColorSupport::parse(&name).ok_or_else(|| {
+                        invalid(format!(
+                            "unknown colour mode `{name}`, use truecolor, 256, 16 or mono"
+                        ))
+                    })?;
+                }
+                "--glyphs" => {
+                    let name = value_of(&arg, args.next())?;
+                    glyphs = GlyphSet::by_name(&name).ok_or_else(|| {
+                        invalid(format!(
+                            "unknown glyph set `{name}`, use unicode, ascii or auto"
+                        ))
+                    })?;
+                }
+                "--ascii" => glyphs = &ASCII,
+                "--sound" => {
+                    let name = value_of(&arg, args.next())?;
+                    sound = SoundChoice::parse(&name)
+                        .ok_or_else(|| invalid(format!("unknown sound backend `{name}`")))?;
+                }
+                "--movement" => {
+                    movement.analog = match value_of(&arg, args.next())?.as_str() {
+                        "analog" => true,
+                        "classic" => false,
+                        name => {
+                            return Err(invalid(format!(
+                                "unknown movement `{name}`, use analog or classic"
+                            )))
+                        }
+                    };
+                }
+                "--max-speed" => {
+                    movement.max_speed = number_of(&arg, args.next())?;
+                    if !(movement.max_speed > 0.0 && movement.max_speed <= 5.0) {
+                        return Err(invalid(format!("`{arg}` must be above 0 and at most 5")));
+                    }
+                }
+                "--inertia" => {
+                    movement.inertia = number_of(&arg, args.next())?;
+                    if !(0.0..1.0).contains(&movement.inertia) {
+                        return Err(invalid(format!("`{arg}` must be from 0 to below 1")));
+                    }
+                }
+                "--controls" => {
+                    let name = value_of(&arg, args.next())?;
+                    controls = Controls::parse(&name).ok_or_else(|| {
+                        invalid(format!("unknown controls `{name}`, use free or throttle"))
+                    })?;
+                }
+                "--fuel-warning" => {
+                    let level = number_of(&arg, args.next())?;
+                    if !(0.0..=100.0).contains(&level) {
+                        return Err(invalid(format!("`{arg}` is a percentage, from 0 to 100")));
+                    }
+                    fuel_warning.level = level as u8;
+                }
+                "--fuel-bell" => {
+                    fuel_warning.bell = match value_of(&arg, args.next())?.as_str() {
+                        "on" => true,
+                        "off" => false,
+                        value => {
+                            return Err(invalid(format!("`{arg}` is on or off, not `{value}`")))
+                        }
+                    };
+                }
+                "--content" => {
+                    content = Some(Content::load(value_of(&arg, args.next())?)?);
+                }
+                "--level" => level = Some(value_of(&arg, args.next())?),
+                "--edit" => edit = Some(value_of(&arg, args.next())?),
+                "--mouse" => mouse = true,
+                "--redraw-every" => {
+                    let seconds = number_of(&arg, args.next())?;
+                    if !(seconds > 0.0 && seconds <= 3600.0) {
+                        return Err(invalid(format!(
+                            "`{arg}` must be above 0 and at most 3600 seconds"
+                        )));
+                    }
+                    redraw_every = Some(Duration::from_secs_f32(seconds));
+                }
+                "-h" | "--help" => return Err(invalid(usage())),
+                _ => return Err(invalid(format!("unknown option `{arg}`\n\n{}", usage()))),
+            }
+        }
+
+        // the kinds a level places are those of the pack, whatever the order
+        let content = content.unwrap_or_else(|| Content::built_in().clone());
+        // a level edited for the first time starts blank
+        let level = match edit.clone().or(level) {
+            Some(path) if edit.is_some() && !Path::new(&path).exists() => Some(Level::blank()),
+            Some(path) => Some(Level::load(path, &content)?),
+            None => None,
+        };
+
+        Ok(Options {
+            theme: theme.adapted(color_support),
+            glyphs,
+            sound,
+            movement,
+            controls,
+            fuel_warning,
+            content,
+            level,
+            edit: edit.map(PathBuf::from),
+            mouse,
+            redraw_every,
+        })
+    }
+
+    /// Switch to the next built-in theme
+    pub fn next_theme(&mut self) {
+        let current = Theme::BUILT_IN
+            .iter()
+            .position(|name| *name == self.theme.name);
+        let next = current.map_or(0, |index| (index + 1) % Theme::BUILT_IN.len());
+        self.theme = Theme::built_in(Theme::BUILT_IN[next])
+            .unwrap()
+            .adapted(self.theme.support);
+    }
+
+    pub fn next_glyphs(&mut self) {
+        self.glyphs = if self.glyphs.name == UNICODE.name {
+            &ASCII
+        } else {
+            &UNICODE
+        };
+    }
+
+    pub fn next_sound(&mut self) {
+        let index = SoundChoice::ALL
+            .iter()
+            .position(|choice| *choice == self.sound);
+        self.sound =
+            SoundChoice::ALL[index.map_or(0, |index| (index + 1) % SoundChoice::ALL.len())];
+    }
+
+    pub fn next_movement(&mut self) {
+        self.movement.analog = !self.movement.analog;
+    }
+
+    pub fn toggle_fuel_bell(&mut self) {
+        self.fuel_warning.bell = !self.fuel_warning.bell;
+    }
+
+    pub fn next_controls(&mut self) {
+        self.controls = match self.controls {
+            Controls::Free => Controls::Throttle,
+            Controls::Throttle => Controls::Free,
+        };
+    }
+}
+
+fn value_of(option: &str, value: Option<String>) -> io::Result<String> {
+    value.ok_or_else(|| invalid(format!("`{option}` needs a value")))
+}
+
+fn number_of(option: &str, value: Option<String>) -> io::Result<f32> {
+    let value = value_of(option, value)?;
+    value
+        .parse()
+        .map_err(|_| invalid(format!("`{option}` needs a number, not `{value}`")))
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn usage() -> String {
+    format!(
+        "usage: riverriderust [options]\n\
+         \n  --theme <name|file>   one of {} or a theme file\
+         \n  --colors <mode>       truecolor, 256, 16 or mono (detected by default)\
+         \n  --glyphs <set>        unicode, ascii or auto (default)\
+         \n  --ascii               same as `--glyphs ascii`\
+         \n  --mouse               steer toward the pointer, click to fire and in the menus\
+         \n  --redraw-every <s>    draw the whole screen again every so many seconds, for flaky links\
+         \n  --sound <backend>     bell (default), tones (with the `audio` feature) or off\
+         \n  --movement <kind>     classic one cell steps (default) or analog\
+         \n  --max-speed <cells>   analog top speed in cells a frame (default 1.5)\
+         \n  --inertia <amount>    analog inertia, from 0 to below 1 (default 0.6)\
+         \n  --controls <scheme>   free (default) or throttle, where up and down change the speed\
+         \n  --fuel-warning <pct>  warn below this much fuel (default 25)\
+         \n  --fuel-bell <on|off>  ring when low on fuel (default on)\
+         \n  --content <dir>       a content pack, the .toml files of the directory\
+         \n  --level <file>        play a level file instead of the random river\
+         \n  --edit <file>         edit a level file, made when it does not exist yet",
+        Theme::BUILT_IN.join(", ")
+    )
+}
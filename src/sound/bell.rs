@@ -0,0 +1,15 @@
+use std::io::{stdout, Write};
+
+use super::{SoundBackend, SoundEvent};
+
+/// Rings the terminal bell for the events worth an interruption.
+pub struct BellBackend;
+
+impl SoundBackend for BellBackend {
+    fn play(&mut self, event: SoundEvent) {
+        if matches!(event, SoundEvent::LowFuel | SoundEvent::Crash) {
+            let mut stdout = stdout();
+            let _ = stdout.write_all(b"\x07").and_then(|_| stdout.flush());
+        }
+    }
+}
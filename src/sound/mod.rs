@@ -0,0 +1,152 @@
+//! Sound effects.
+//!
+//! The game only talks to a [`SoundBus`]: `physics.rs` emits [`SoundEvent`]s
+//! and once per frame the bus hands them to the [`SoundBackend`] the player
+//! picked, if any.
+
+mod bell;
+#[cfg(feature = "audio")]
+mod tones;
+
+pub use bell::BellBackend;
+#[cfg(feature = "audio")]
+pub use tones::ToneBackend;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SoundEvent {
+    ShotFired,
+    EnemyDestroyed,
+    FuelDestroyed,
+    FuelPickedUp,
+    WeaponPickedUp,
+    PowerUpPicked,
+    /// The shield took a hit for the plane
+    ShieldHit,
+    LowFuel,
+    Crash,
+}
+
+pub trait SoundBackend {
+    fn play(&mut self, event: SoundEvent);
+}
+
+/// Collects the sounds of a frame and plays each of them once.
+#[derive(Default)]
+pub struct SoundBus {
+    backend: Option<Box<dyn SoundBackend>>,
+    queue: Vec<SoundEvent>,
+}
+
+impl SoundBus {
+    pub fn new(backend: Box<dyn SoundBackend>) -> SoundBus {
+        SoundBus {
+            backend: Some(backend),
+            queue: Vec::new(),
+        }
+    }
+
+    /// A bus without backend, the events are dropped.
+    pub fn muted() -> SoundBus {
+        SoundBus::default()
+    }
+
+    pub fn emit(&mut self, event: SoundEvent) {
+        if self.backend.is_some() && !self.queue.contains(&event) {
+            self.queue.push(event);
+        }
+    }
+
+    /// Play what was emitted since the last flush.
+    pub fn flush(&mut self) {
+        if let Some(backend) = self.backend.as_mut() {
+            for event in self.queue.drain(..) {
+                backend.play(event);
+            }
+        }
+    }
+} // end of SoundBus implementation.
+
+/// Records the events instead of playing them.
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct NullBackend {
+    played: std::rc::Rc<std::cell::RefCell<Vec<SoundEvent>>>,
+}
+
+#[cfg(test)]
+impl NullBackend {
+    pub fn played(&self) -> Vec<SoundEvent> {
+        self.played.borrow().clone()
+    }
+}
+
+#[cfg(test)]
+impl SoundBackend for NullBackend {
+    fn play(&mut self, event: SoundEvent) {
+        self.played.borrow_mut().push(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        content::Content,
+        entities::{DeathCause, PlayerStatus},
+        world::World,
+    };
+
+    fn world_with_recorder() -> (World, NullBackend) {
+        let null = NullBackend::default();
+        let world = World::new(80, 30).with_sound(SoundBus::new(Box::new(null.clone())));
+        (world, null)
+    }
+
+    #[test]
+    fn events_are_played_once_per_frame() {
+        let null = NullBackend::default();
+        let mut bus = SoundBus::new(Box::new(null.clone()));
+        bus.emit(SoundEvent::ShotFired);
+        bus.emit(SoundEvent::ShotFired);
+        bus.emit(SoundEvent::Crash);
+        assert!(null.played().is_empty());
+
+        bus.flush();
+        bus.flush();
+        assert_eq!(
+            null.played(),
+            vec![SoundEvent::ShotFired, SoundEvent::Crash]
+        );
+    }
+
+    #[test]
+    fn physics_emits_gameplay_sounds() {
+        let (mut world, null) = world_with_recorder();
+        world.fire();
+        let bullet = world.entities.position[0].clone();
+        world
+            .entities
+            .spawn(Content::built_in().kind("enemy").unwrap(), bullet);
+        world.physics();
+        world.sound.flush();
+        assert_eq!(
+            null.played(),
+            vec![SoundEvent::ShotFired, SoundEvent::EnemyDestroyed]
+        );
+    }
+
+    #[test]
+    fn crash_and_low_fuel() {
+        let (mut world, null) = world_with_recorder();
+        world.player.gas = 401;
+        world.physics();
+        world.sound.flush();
+        assert_eq!(null.played(), vec![SoundEvent::LowFuel]);
+
+        world.player.location.c = 0;
+        world.physics();
+        world.sound.flush();
+        assert_eq!(world.player.status, PlayerStatus::Dead(DeathCause::Ground));
+        assert_eq!(null.played().last(), Some(&SoundEvent::Crash));
+    }
+}
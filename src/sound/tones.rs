@@ -0,0 +1,129 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+    sync::mpsc::{channel, Sender},
+    thread,
+};
+
+use super::{SoundBackend, SoundEvent};
+
+const SAMPLE_RATE: u32 = 22050;
+
+/// Plays generated square wave tones through `aplay`.
+///
+/// The samples are written from a background thread so a slow sound card
+/// never holds a frame back. Without `aplay` the backend stays silent.
+pub struct ToneBackend {
+    samples: Option<Sender<Vec<u8>>>,
+}
+
+/// A tone sliding from one frequency to another.
+struct Tone {
+    from: f32,
+    to: f32,
+    millis: u32,
+}
+
+impl ToneBackend {
+    pub fn new() -> ToneBackend {
+        let player = Command::new("aplay")
+            .args(["-q", "-t", "raw", "-f", "U8", "-c", "1", "-r"])
+            .arg(SAMPLE_RATE.to_string())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+
+        let Ok(mut player) = player else {
+            return ToneBackend { samples: None };
+        };
+
+        let (sender, receiver) = channel::<Vec<u8>>();
+        let mut input = player.stdin.take().unwrap();
+        thread::spawn(move || {
+            for samples in receiver {
+                if input.write_all(&samples).is_err() {
+                    break;
+                }
+            }
+            drop(input);
+            let _ = player.wait();
+        });
+
+        ToneBackend {
+            samples: Some(sender),
+        }
+    }
+}
+
+const fn tone(from: f32, to: f32, millis: u32) -> Tone {
+    Tone { from, to, millis }
+}
+
+fn tones(event: SoundEvent) -> Vec<Tone> {
+    match event {
+        SoundEvent::ShotFired => vec![tone(1400.0, 700.0, 40)],
+        SoundEvent::EnemyDestroyed => vec![tone(300.0, 80.0, 120)],
+        SoundEvent::FuelDestroyed => vec![tone(500.0, 60.0, 200)],
+        SoundEvent::FuelPickedUp => vec![tone(660.0, 660.0, 50), tone(990.0, 990.0, 70)],
+        SoundEvent::WeaponPickedUp => vec![tone(440.0, 880.0, 60), tone(880.0, 880.0, 60)],
+        SoundEvent::PowerUpPicked => vec![
+            tone(523.0, 523.0, 50),
+            tone(659.0, 659.0, 50),
+            tone(784.0, 784.0, 80),
+        ],
+        SoundEvent::ShieldHit => vec![tone(900.0, 150.0, 250)],
+        SoundEvent::LowFuel => vec![
+            tone(880.0, 880.0, 80),
+            tone(0.0, 0.0, 40),
+            tone(880.0, 880.0, 80),
+        ],
+        SoundEvent::Crash => vec![tone(200.0, 30.0, 600)],
+    }
+}
+
+/// 8 bit unsigned mono samples, a frequency of 0 is silence.
+fn synthesize(tones: &[Tone]) -> Vec<u8> {
+    let mut samples = Vec::new();
+    for tone in tones {
+        let count = SAMPLE_RATE * tone.millis / 1000;
+        let mut phase = 0.0f32;
+        for i in 0..count {
+            let progress = i as f32 / count as f32;
+            let frequency = tone.from + (tone.to - tone.from) * progress;
+            phase = (phase + frequency / SAMPLE_RATE as f32).fract();
+
+            let sample = if frequency <= 0.0 {
+                128
+            } else if phase < 0.5 {
+                128 + 40
+            } else {
+                128 - 40
+            };
+            samples.push(sample);
+        }
+    }
+    samples
+}
+
+impl SoundBackend for ToneBackend {
+    fn play(&mut self, event: SoundEvent) {
+        if let Some(sender) = &self.samples {
+            if sender.send(synthesize(&tones(event))).is_err() {
+                self.samples = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn synthesizes_the_right_length() {
+        let samples = synthesize(&tones(SoundEvent::LowFuel));
+        assert_eq!(samples.len() as u32, SAMPLE_RATE * 200 / 1000);
+        assert!(samples.iter().any(|&s| s != 128));
+    }
+}
@@ -0,0 +1,331 @@
+//! Sprites made of one or more cells.
+//!
+//! A [`Sprite`] is a small grid of characters drawn around an anchor cell,
+//! which is the cell at the entity's [`Location`]. The cells that are not
+//! transparent make the footprint used for collisions.
+
+use crossterm::style::ContentStyle;
+
+use crate::entities::Location;
+
+/// The characters of a sprite, spaces are transparent.
+#[derive(Debug)]
+pub struct SpriteArt {
+    pub rows: &'static [&'static str],
+    /// Column and line of the cell placed at the entity's location.
+    pub anchor: (u16, u16),
+}
+
+impl SpriteArt {
+    /// A single character sprite.
+    pub const fn single(rows: &'static [&'static str]) -> SpriteArt {
+        SpriteArt {
+            rows,
+            anchor: (0, 0),
+        }
+    }
+
+    /// The rectangle around the art anchored at `at`, the same as the
+    /// [`Sprite::bounds`] of its sprites.
+    pub fn bounds(&self, at: &Location) -> Rect {
+        let width = self
+            .rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+        let height = if width == 0 { 0 } else { self.rows.len() };
+        Rect::new(
+            at.c as i32 - self.anchor.0 as i32,
+            at.l as i32 - self.anchor.1 as i32,
+            width as i32,
+            height as i32,
+        )
+    }
+}
+
+/// A rectangle of cells, `c` and `l` are its top left corner.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Rect {
+    pub c: i32,
+    pub l: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    pub fn new(c: i32, l: i32, width: i32, height: i32) -> Rect {
+        Rect {
+            c,
+            l,
+            width,
+            height,
+        }
+    }
+
+    pub fn right(&self) -> i32 {
+        self.c + self.width
+    }
+
+    pub fn bottom(&self) -> i32 {
+        self.l + self.height
+    }
+
+    pub fn overlaps(&self, other: &Rect) -> bool {
+        self.c < other.right()
+            && other.c < self.right()
+            && self.l < other.bottom()
+            && other.l < self.bottom()
+    }
+
+    /// The rectangle moved by `(c, l)` cells.
+    pub fn moved(self, (c, l): (i32, i32)) -> Rect {
+        Rect::new(self.c + c, self.l + l, self.width, self.height)
+    }
+
+    /// The smallest rectangle around both.
+    pub fn union(self, other: Rect) -> Rect {
+        let (c, l) = (self.c.min(other.c), self.l.min(other.l));
+        Rect::new(
+            c,
+            l,
+            self.right().max(other.right()) - c,
+            self.bottom().max(other.bottom()) - l,
+        )
+    }
+} // end of Rect implementation.
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub character: char,
+    pub style: ContentStyle,
+}
+
+#[derive(Clone)]
+pub struct Sprite {
+    width: u16,
+    anchor: (u16, u16),
+    cells: Vec<Option<Cell>>,
+}
+
+impl Sprite {
+    pub fn new(art: &SpriteArt, style: ContentStyle) -> Sprite {
+        let width = art
+            .rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut cells = Vec::with_capacity(width * art.rows.len());
+        for row in art.rows {
+            let mut chars = row.chars();
+            for _ in 0..width {
+                cells.push(match chars.next() {
+                    Some(' ') | None => None,
+                    Some(character) => Some(Cell { character, style }),
+                });
+            }
+        }
+
+        Sprite {
+            width: width as u16,
+            anchor: art.anchor,
+            cells,
+        }
+    }
+
+    /// Give a single cell of the sprite its own style.
+    pub fn with_cell_style(mut self, c: u16, l: u16, style: ContentStyle) -> Sprite {
+        let index = (l * self.width + c) as usize;
+        if let Some(Some(cell)) = self.cells.get_mut(index) {
+            cell.style = style;
+        }
+        self
+    }
+
+    /// The visible cells with their screen position when anchored at `at`.
+    ///
+    /// Positions may fall outside of the screen, so they are signed.
+    pub fn cells(&self, at: &Location) -> impl Iterator<Item = ((i32, i32), Cell)> + '_ {
+        let origin_c = at.c as i32 - self.anchor.0 as i32;
+        let origin_l = at.l as i32 - self.anchor.1 as i32;
+        let width = self.width.max(1) as usize;
+
+        self.cells
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, cell)| {
+                let c = origin_c + (index % width) as i32;
+                let l = origin_l + (index / width) as i32;
+                cell.map(|cell| ((c, l), cell))
+            })
+    }
+
+    /// The rectangle around the sprite anchored at `at`, transparent cells included.
+    pub fn bounds(&self, at: &Location) -> Rect {
+        let height = self.cells.len() / self.width.max(1) as usize;
+        Rect::new(
+            at.c as i32 - self.anchor.0 as i32,
+            at.l as i32 - self.anchor.1 as i32,
+            self.width as i32,
+            height as i32,
+        )
+    }
+
+    /// The rectangle covered by the sprite sliding from `from` by `motion`.
+    pub fn swept_bounds(&self, from: &Location, motion: (i32, i32)) -> Rect {
+        let start = self.bounds(from);
+        start.union(start.moved(motion))
+    }
+
+    /// The cells this sprite occupies when anchored at `at`.
+    pub fn footprint(&self, at: &Location) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.cells(at).map(|(position, _)| position)
+    }
+
+    /// Check if two sprites share at least one cell.
+    pub fn overlaps(&self, at: &Location, other: &Sprite, other_at: &Location) -> bool {
+        let others: Vec<(i32, i32)> = other.footprint(other_at).collect();
+        self.footprint(at).any(|cell| others.contains(&cell))
+    }
+
+    /// When this sprite, moving from `from` by `motion` during a frame, first
+    /// shares a cell with `other` standing still at `other_at`.
+    ///
+    /// The cells slide continuously, so a fast sprite cannot jump over a thin
+    /// one. The time goes from 0 (at `from`) to 1 (at `from + motion`),
+    /// `None` when they never meet.
+    pub fn sweep(
+        &self,
+        from: &Location,
+        motion: (i32, i32),
+        other: &Sprite,
+        other_at: &Location,
+    ) -> Option<f32> {
+        let others: Vec<(i32, i32)> = other.footprint(other_at).collect();
+        self.footprint(from)
+            .flat_map(|cell| {
+                others
+                    .iter()
+                    .filter_map(move |&still| first_contact(cell, motion, still))
+            })
+            .min_by(f32::total_cmp)
+    }
+} // end of Sprite implementation.
+
+/// When a cell sliding by `motion` first overlaps the `still` one, from 0 to 1.
+///
+/// Touching sides or corners is not an overlap.
+fn first_contact(cell: (i32, i32), motion: (i32, i32), still: (i32, i32)) -> Option<f32> {
+    // the times the cells overlap on one axis, |gap + speed * t| < 1
+    let axis = |gap: i32, speed: i32| {
+        if speed == 0 {
+            return (gap.abs() < 1).then_some((f32::NEG_INFINITY, f32::INFINITY));
+        }
+        let (a, b) = (
+            (-1 - gap) as f32 / speed as f32,
+            (1 - gap) as f32 / speed as f32,
+        );
+        Some((a.min(b), a.max(b)))
+    };
+
+    let (enter_c, exit_c) = axis(cell.0 - still.0, motion.0)?;
+    let (enter_l, exit_l) = axis(cell.1 - still.1, motion.1)?;
+    let enter = enter_c.max(enter_l).max(0.0);
+    let exit = exit_c.min(exit_l);
+    (enter < exit && enter < 1.0).then_some(enter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const PLANE: SpriteArt = SpriteArt {
+        rows: &[" ^ ", "<#>"],
+        anchor: (1, 1),
+    };
+
+    #[test]
+    fn footprint_skips_transparent_cells() {
+        let plane = Sprite::new(&PLANE, ContentStyle::new());
+        let cells: Vec<_> = plane.footprint(&Location::new(10, 5)).collect();
+        assert_eq!(cells, vec![(10, 4), (9, 5), (10, 5), (11, 5)]);
+    }
+
+    #[test]
+    fn overlapping_uses_real_cells() {
+        let plane = Sprite::new(&PLANE, ContentStyle::new());
+        let dot = Sprite::new(&SpriteArt::single(&["*"]), ContentStyle::new());
+
+        assert!(plane.overlaps(&Location::new(10, 5), &dot, &Location::new(11, 5)));
+        assert!(plane.overlaps(&Location::new(10, 5), &dot, &Location::new(10, 4)));
+        // the transparent corners of the plane
+        assert!(!plane.overlaps(&Location::new(10, 5), &dot, &Location::new(11, 4)));
+        assert!(!plane.overlaps(&Location::new(10, 5), &dot, &Location::new(12, 5)));
+    }
+
+    #[test]
+    fn bounds_cover_the_sprite_and_its_way() {
+        let plane = Sprite::new(&PLANE, ContentStyle::new());
+        let at = Location::new(10, 5);
+        assert_eq!(plane.bounds(&at), Rect::new(9, 4, 3, 2));
+        assert_eq!(plane.swept_bounds(&at, (2, -3)), Rect::new(9, 1, 5, 5));
+        assert!(!plane.bounds(&at).overlaps(&Rect::new(12, 4, 1, 1)));
+    }
+
+    #[test]
+    fn fast_sprites_do_not_jump_over_thin_ones() {
+        let dot = Sprite::new(&SpriteArt::single(&["*"]), ContentStyle::new());
+        let (from, wall) = (Location::new(10, 10), Location::new(10, 6));
+
+        // ends up past the dot, but went through it on the way
+        assert!(!dot.overlaps(&Location::new(10, 2), &dot, &wall));
+        assert_eq!(dot.sweep(&from, (0, -8), &dot, &wall), Some(3.0 / 8.0));
+        assert_eq!(dot.sweep(&from, (0, -3), &dot, &wall), None);
+        // a diagonal just grazing the corner
+        assert_eq!(
+            dot.sweep(&from, (4, -4), &dot, &Location::new(12, 10)),
+            None
+        );
+        assert_eq!(dot.sweep(&wall, (5, 0), &dot, &wall), Some(0.0));
+    }
+
+    proptest! {
+        /// Sampled along the way, the sprites never overlap before the sweep
+        /// says they meet, and always overlap where they end if they do.
+        #[test]
+        fn sweeping_finds_the_first_overlap(
+            from in (0u16..20, 0u16..20),
+            motion in (-6i32..=6, -6i32..=6),
+            at in (0u16..20, 0u16..20),
+        ) {
+            let plane = Sprite::new(&PLANE, ContentStyle::new());
+            let dot = Sprite::new(&SpriteArt::single(&["*"]), ContentStyle::new());
+            let (from, at) = (Location::new(from.0, from.1), Location::new(at.0, at.1));
+            let hit = plane.sweep(&from, motion, &dot, &at);
+
+            let dot_cell = dot.footprint(&at).next().unwrap();
+            for step in 0..=64 {
+                let t = step as f32 / 64.0;
+                let overlaps = plane.footprint(&from).any(|(c, l)| {
+                    let c = c as f32 + motion.0 as f32 * t;
+                    let l = l as f32 + motion.1 as f32 * t;
+                    (c - dot_cell.0 as f32).abs() < 1.0 && (l - dot_cell.1 as f32).abs() < 1.0
+                });
+                if overlaps {
+                    prop_assert!(hit.is_some_and(|hit| hit <= t));
+                }
+            }
+
+            let end = (from.c as i32 + motion.0, from.l as i32 + motion.1);
+            if end.0 >= 0 && end.1 >= 0 {
+                let end = Location::new(end.0 as u16, end.1 as u16);
+                if plane.overlaps(&end, &dot, &at) {
+                    prop_assert!(hit.is_some());
+                }
+            }
+        }
+    }
+}
@@ -4,7 +4,7 @@ use std::{fmt::Display, io::Stdout};
 
 use crossterm::{cursor::MoveTo, style::Print, terminal::Clear, QueueableCommand};
 
-use crate::entities::{Bullet, Enemy, Fuel, Location, Player};
+use crate::entities::{Location, Player};
 
 pub type StdoutResult<'a> = Result<&'a mut Stdout, std::io::Error>;
 
@@ -18,24 +18,6 @@ impl AsLocationTuple for Location {
     }
 }
 
-impl AsLocationTuple for &Enemy {
-    fn as_loc_tuple(&self) -> (u16, u16) {
-        self.location.as_loc_tuple()
-    }
-}
-
-impl AsLocationTuple for &Bullet {
-    fn as_loc_tuple(&self) -> (u16, u16) {
-        self.location.as_loc_tuple()
-    }
-}
-
-impl AsLocationTuple for &Fuel {
-    fn as_loc_tuple(&self) -> (u16, u16) {
-        self.location.as_loc_tuple()
-    }
-}
-
 impl AsLocationTuple for &Player {
     fn as_loc_tuple(&self) -> (u16, u16) {
         self.location.as_loc_tuple()
@@ -61,30 +43,30 @@ impl AsLocationTuple for u16 {
 }
 
 pub trait StdoutExt {
-    fn clear_all(&mut self) -> StdoutResult;
+    fn clear_all(&mut self) -> StdoutResult<'_>;
 
-    fn move_cursor(&mut self, loc: impl AsLocationTuple) -> StdoutResult;
+    fn move_cursor(&mut self, loc: impl AsLocationTuple) -> StdoutResult<'_>;
 
-    fn print(&mut self, display: impl Display) -> StdoutResult;
+    fn print(&mut self, display: impl Display) -> StdoutResult<'_>;
 
-    fn draw(&mut self, loc: impl AsLocationTuple, display: impl Display) -> StdoutResult;
+    fn draw(&mut self, loc: impl AsLocationTuple, display: impl Display) -> StdoutResult<'_>;
 }
 
 impl StdoutExt for Stdout {
-    fn move_cursor(&mut self, loc: impl AsLocationTuple) -> StdoutResult {
+    fn move_cursor(&mut self, loc: impl AsLocationTuple) -> StdoutResult<'_> {
         let (c, l) = loc.as_loc_tuple();
         self.queue(MoveTo(c, l))
     }
 
-    fn draw(&mut self, loc: impl AsLocationTuple, display: impl Display) -> StdoutResult {
+    fn draw(&mut self, loc: impl AsLocationTuple, display: impl Display) -> StdoutResult<'_> {
         self.move_cursor(loc)?.print(display)
     }
 
-    fn clear_all(&mut self) -> StdoutResult {
+    fn clear_all(&mut self) -> StdoutResult<'_> {
         self.queue(Clear(crossterm::terminal::ClearType::All))
     }
 
-    fn print(&mut self, display: impl Display) -> StdoutResult {
+    fn print(&mut self, display: impl Display) -> StdoutResult<'_> {
         self.queue(Print(display))
     }
 }
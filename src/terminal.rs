@@ -0,0 +1,197 @@
+//! The terminal the game takes over, and gives back whatever happens.
+//!
+//! [`Terminal::enter`] switches to the alternate screen, raw mode and the
+//! other modes the game needs; they are undone when the guard is dropped,
+//! and by a panic hook before the panic is printed, so a crash never leaves
+//! a broken shell behind.
+//!
+//! SIGINT (or Ctrl-C, a key in raw mode) and SIGTERM ask the game to quit,
+//! SIGTSTP (or Ctrl-Z) to be suspended: the terminal is given back while the process
+//! is stopped, and taken again when it is continued. SIGCONT takes it again
+//! too, after a stop the game did not see coming.
+
+use std::{
+    io::{self, stdout, Stdout},
+    panic,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, Once, PoisonError,
+    },
+};
+
+use crossterm::{
+    cursor::{Hide, Show},
+    event::{
+        DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture,
+        KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
+    terminal::{
+        disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
+    ExecutableCommand,
+};
+
+use crate::stout_ext::StdoutExt;
+
+/// The modes turned on, for the panic hook to turn them off too; `None`
+/// once the terminal is given back.
+static MODES: Mutex<Option<Modes>> = Mutex::new(None);
+
+#[derive(Clone, Copy)]
+struct Modes {
+    /// The keyboard reports releases
+    enhanced: bool,
+    mouse: bool,
+}
+
+/// What the signals asked for.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Signal {
+    Quit,
+    Suspend,
+    /// Stopped and continued, the terminal may be as the shell left it
+    Continue,
+}
+
+pub struct Terminal {
+    stdout: Stdout,
+    modes: Modes,
+    /// Raised by SIGINT and SIGTERM
+    quit: Arc<AtomicBool>,
+    /// Raised by SIGTSTP
+    suspend: Arc<AtomicBool>,
+    /// Raised by SIGCONT
+    resume: Arc<AtomicBool>,
+}
+
+impl Terminal {
+    /// Take the terminal over, with the mouse captured if `mouse`.
+    pub fn enter(mouse: bool) -> io::Result<Terminal> {
+        static HOOK: Once = Once::new();
+        HOOK.call_once(|| {
+            let print = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                restore();
+                print(info);
+            }));
+        });
+
+        let mut terminal = Terminal {
+            stdout: stdout(),
+            modes: Modes {
+                enhanced: false,
+                mouse,
+            },
+            quit: Arc::default(),
+            suspend: Arc::default(),
+            resume: Arc::default(),
+        };
+        #[cfg(unix)]
+        {
+            use signal_hook::{consts::*, flag::register};
+            register(SIGINT, terminal.quit.clone())?;
+            register(SIGTERM, terminal.quit.clone())?;
+            register(SIGTSTP, terminal.suspend.clone())?;
+            register(SIGCONT, terminal.resume.clone())?;
+        }
+
+        terminal.setup()?;
+        Ok(terminal)
+    }
+
+    fn setup(&mut self) -> io::Result<()> {
+        // given back even if this fails half way
+        self.modes.enhanced = false;
+        *MODES.lock().unwrap_or_else(PoisonError::into_inner) = Some(self.modes);
+        self.stdout
+            .execute(EnterAlternateScreen)?
+            .execute(Hide)?
+            .execute(EnableFocusChange)?;
+        enable_raw_mode()?;
+        self.stdout.clear_all()?;
+
+        // key releases, so held keys can be told from repeated ones
+        self.modes.enhanced = supports_keyboard_enhancement().unwrap_or(false);
+        if self.modes.enhanced {
+            self.stdout.execute(PushKeyboardEnhancementFlags(
+                KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                    | KeyboardEnhancementFlags::REPORT_EVENT_TYPES,
+            ))?;
+        }
+        if self.modes.mouse {
+            self.stdout.execute(EnableMouseCapture)?;
+        }
+        *MODES.lock().unwrap_or_else(PoisonError::into_inner) = Some(self.modes);
+        Ok(())
+    }
+
+    /// The terminal reports key releases
+    pub fn enhanced(&self) -> bool {
+        self.modes.enhanced
+    }
+
+    pub fn stdout(&mut self) -> &mut Stdout {
+        &mut self.stdout
+    }
+
+    /// Ask to quit the way SIGINT does, for Ctrl-C read as a key.
+    pub fn interrupt(&self) {
+        self.quit.store(true, Ordering::Relaxed);
+    }
+
+    /// What the signals asked for since the last call, quitting first.
+    pub fn signal(&self) -> Option<Signal> {
+        if self.quit.swap(false, Ordering::Relaxed) {
+            Some(Signal::Quit)
+        } else if self.suspend.swap(false, Ordering::Relaxed) {
+            Some(Signal::Suspend)
+        } else if self.resume.swap(false, Ordering::Relaxed) {
+            Some(Signal::Continue)
+        } else {
+            None
+        }
+    }
+
+    /// Give the terminal back and stop, the way Ctrl-Z does; once continued
+    /// take it again. The screen has to be drawn anew.
+    pub fn suspend(&mut self) -> io::Result<()> {
+        restore();
+        #[cfg(unix)]
+        signal_hook::low_level::emulate_default_handler(signal_hook::consts::SIGTSTP)?;
+        // continued, the SIGCONT is this one
+        self.resume.store(false, Ordering::Relaxed);
+        self.setup()
+    }
+
+    /// Take the terminal again, after a stop by someone else.
+    pub fn resume(&mut self) -> io::Result<()> {
+        restore();
+        self.setup()
+    }
+} // end of Terminal implementation.
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// Undo the modes, once; errors are ignored, there is nothing left to do
+/// about them.
+fn restore() {
+    let Some(modes) = MODES.lock().unwrap_or_else(PoisonError::into_inner).take() else {
+        return;
+    };
+    let mut stdout = stdout();
+    if modes.mouse {
+        let _ = stdout.execute(DisableMouseCapture);
+    }
+    if modes.enhanced {
+        let _ = stdout.execute(PopKeyboardEnhancementFlags);
+    }
+    let _ = stdout.execute(DisableFocusChange);
+    let _ = disable_raw_mode();
+    let _ = stdout.execute(Show);
+    let _ = stdout.execute(LeaveAlternateScreen);
+}
@@ -0,0 +1,549 @@
+//! Colour themes for the river, the sprites and the HUD.
+//!
+//! A [`Theme`] is either one of the built-in themes or a TOML file. Before it
+//! reaches the [`Canvas`](crate::canvas::Canvas) it is adapted to what the
+//! terminal can show, see [`ColorSupport`].
+
+use std::{env, fs, io, path::Path};
+
+use crossterm::style::{Color, ContentStyle, Stylize};
+use serde::Deserialize;
+
+/// How many colours the terminal can display.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorSupport {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+    Monochrome,
+}
+
+impl ColorSupport {
+    /// Guess the colour support from the usual environment variables.
+    pub fn detect() -> ColorSupport {
+        let var = |name: &str| env::var(name).unwrap_or_default().to_lowercase();
+
+        if env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            return ColorSupport::Monochrome;
+        }
+
+        if matches!(var("COLORTERM").as_str(), "truecolor" | "24bit") {
+            return ColorSupport::TrueColor;
+        }
+
+        let term = var("TERM");
+        if term == "dumb" {
+            ColorSupport::Monochrome
+        } else if term.contains("256color") {
+            ColorSupport::Ansi256
+        } else {
+            ColorSupport::Ansi16
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<ColorSupport> {
+        match name {
+            "truecolor" | "24bit" => Some(ColorSupport::TrueColor),
+            "256" => Some(ColorSupport::Ansi256),
+            "16" => Some(ColorSupport::Ansi16),
+            "mono" | "none" => Some(ColorSupport::Monochrome),
+            _ => None,
+        }
+    }
+
+    /// Convert a colour to the closest one this terminal can display.
+    pub fn adapt(self, color: Color) -> Color {
+        match (self, color) {
+            (_, Color::Reset) => Color::Reset,
+            (ColorSupport::Monochrome, _) => Color::Reset,
+            (ColorSupport::TrueColor, color) => color,
+            (ColorSupport::Ansi256, Color::Rgb { r, g, b }) => {
+                Color::AnsiValue(rgb_to_ansi256(r, g, b))
+            }
+            (ColorSupport::Ansi256, color) => color,
+            (ColorSupport::Ansi16, Color::Rgb { r, g, b }) => rgb_to_ansi16(r, g, b),
+            (ColorSupport::Ansi16, Color::AnsiValue(value)) => {
+                let (r, g, b) = ansi256_to_rgb(value);
+                rgb_to_ansi16(r, g, b)
+            }
+            (ColorSupport::Ansi16, color) => color,
+        }
+    }
+}
+
+/// The colours used to draw the game.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub bank: Color,
+    pub water: Color,
+    pub enemy: Color,
+    pub fuel: Color,
+    pub bullet: Color,
+    pub player: Color,
+    pub powerup: Color,
+    pub hud_fg: Color,
+    pub hud_bg: Color,
+    /// The fuel gauge, from a full tank to an empty one
+    pub gauge_ok: Color,
+    pub gauge_low: Color,
+    pub gauge_critical: Color,
+    /// Character the banks are filled with, useful when there is no colour.
+    pub bank_fill: char,
+    /// What the theme was adapted to, used for computed colours.
+    pub support: ColorSupport,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::classic()
+    }
+}
+
+impl Theme {
+    pub const BUILT_IN: [&'static str; 4] = ["classic", "vivid", "colorblind", "mono"];
+
+    /// The original colours of the game.
+    pub fn classic() -> Theme {
+        Theme {
+            name: "classic".into(),
+            bank: Color::Green,
+            water: Color::Blue,
+            enemy: Color::Red,
+            fuel: Color::Yellow,
+            bullet: Color::Cyan,
+            player: Color::White,
+            powerup: Color::Magenta,
+            hud_fg: Color::Black,
+            hud_bg: Color::White,
+            gauge_ok: Color::Green,
+            gauge_low: Color::Yellow,
+            gauge_critical: Color::Red,
+            bank_fill: ' ',
+            support: ColorSupport::TrueColor,
+        }
+    }
+
+    /// Softer truecolor version of the classic theme.
+    pub fn vivid() -> Theme {
+        Theme {
+            name: "vivid".into(),
+            bank: rgb(0x3b, 0x7d, 0x23),
+            water: rgb(0x1e, 0x4f, 0x9c),
+            enemy: rgb(0xe5, 0x39, 0x35),
+            fuel: rgb(0xff, 0xd5, 0x4f),
+            bullet: rgb(0x80, 0xde, 0xea),
+            player: rgb(0xfa, 0xfa, 0xfa),
+            powerup: rgb(0xab, 0x47, 0xbc),
+            hud_fg: rgb(0x21, 0x21, 0x21),
+            hud_bg: rgb(0xee, 0xee, 0xee),
+            gauge_ok: rgb(0x66, 0xbb, 0x6a),
+            gauge_low: rgb(0xff, 0xa7, 0x26),
+            gauge_critical: rgb(0xe5, 0x39, 0x35),
+            bank_fill: ' ',
+            support: ColorSupport::TrueColor,
+        }
+    }
+
+    /// Okabe-Ito palette, distinguishable with the common colour blindnesses.
+    pub fn colorblind() -> Theme {
+        Theme {
+            name: "colorblind".into(),
+            bank: rgb(0xe6, 0x9f, 0x00),
+            water: rgb(0x00, 0x72, 0xb2),
+            enemy: rgb(0xd5, 0x5e, 0x00),
+            fuel: rgb(0xf0, 0xe4, 0x42),
+            bullet: rgb(0x56, 0xb4, 0xe9),
+            player: rgb(0xff, 0xff, 0xff),
+            powerup: rgb(0xcc, 0x79, 0xa7),
+            hud_fg: rgb(0x00, 0x00, 0x00),
+            hud_bg: rgb(0xff, 0xff, 0xff),
+            gauge_ok: rgb(0x00, 0x9e, 0x73),
+            gauge_low: rgb(0xe6, 0x9f, 0x00),
+            gauge_critical: rgb(0xd5, 0x5e, 0x00),
+            bank_fill: ' ',
+            support: ColorSupport::TrueColor,
+        }
+    }
+
+    /// No colours at all, banks are drawn with characters.
+    pub fn mono() -> Theme {
+        Theme {
+            name: "mono".into(),
+            bank: Color::Reset,
+            water: Color::Reset,
+            enemy: Color::Reset,
+            fuel: Color::Reset,
+            bullet: Color::Reset,
+            player: Color::Reset,
+            powerup: Color::Reset,
+            hud_fg: Color::Reset,
+            hud_bg: Color::Reset,
+            gauge_ok: Color::Reset,
+            gauge_low: Color::Reset,
+            gauge_critical: Color::Reset,
+            bank_fill: '#',
+            support: ColorSupport::TrueColor,
+        }
+    }
+
+    pub fn built_in(name: &str) -> Option<Theme> {
+        match name {
+            "classic" => Some(Theme::classic()),
+            "vivid" => Some(Theme::vivid()),
+            "colorblind" => Some(Theme::colorblind()),
+            "mono" => Some(Theme::mono()),
+            _ => None,
+        }
+    }
+
+    /// Load a built-in theme by name, or a theme file by path.
+    pub fn load(name_or_path: &str) -> io::Result<Theme> {
+        match Theme::built_in(name_or_path) {
+            Some(theme) => Ok(theme),
+            None => Theme::from_file(name_or_path),
+        }
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Theme> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|err| {
+            io::Error::new(
+                err.kind(),
+                format!("cannot read theme {}: {err}", path.display()),
+            )
+        })?;
+        Theme::from_toml(&content).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid theme {}: {err}", path.display()),
+            )
+        })
+    }
+
+    /// Parse a theme file. Missing keys are taken from the `base` theme.
+    pub fn from_toml(content: &str) -> Result<Theme, String> {
+        let file: ThemeFile = toml::from_str(content).map_err(|err| err.message().to_string())?;
+
+        let mut theme = match &file.base {
+            Some(base) => Theme::built_in(base).ok_or(format!("unknown base theme `{base}`"))?,
+            None => Theme::classic(),
+        };
+        theme.name = file.name.unwrap_or_else(|| "custom".into());
+
+        let slots = [
+            ("bank", file.bank, &mut theme.bank),
+            ("water", file.water, &mut theme.water),
+            ("enemy", file.enemy, &mut theme.enemy),
+            ("fuel", file.fuel, &mut theme.fuel),
+            ("bullet", file.bullet, &mut theme.bullet),
+            ("player", file.player, &mut theme.player),
+            ("powerup", file.powerup, &mut theme.powerup),
+            ("hud_fg", file.hud_fg, &mut theme.hud_fg),
+            ("hud_bg", file.hud_bg, &mut theme.hud_bg),
+            ("gauge_ok", file.gauge_ok, &mut theme.gauge_ok),
+            ("gauge_low", file.gauge_low, &mut theme.gauge_low),
+            (
+                "gauge_critical",
+                file.gauge_critical,
+                &mut theme.gauge_critical,
+            ),
+        ];
+        for (key, value, slot) in slots {
+            if let Some(value) = value {
+                *slot =
+                    parse_color(&value).ok_or(format!("invalid colour `{value}` for `{key}`"))?;
+            }
+        }
+
+        if let Some(fill) = file.bank_fill {
+            theme.bank_fill = fill;
+        }
+
+        Ok(theme)
+    }
+
+    /// Downgrade the theme to what the terminal can display.
+    pub fn adapted(mut self, support: ColorSupport) -> Theme {
+        for color in [
+            &mut self.bank,
+            &mut self.water,
+            &mut self.enemy,
+            &mut self.fuel,
+            &mut self.bullet,
+            &mut self.player,
+            &mut self.powerup,
+            &mut self.hud_fg,
+            &mut self.hud_bg,
+            &mut self.gauge_ok,
+            &mut self.gauge_low,
+            &mut self.gauge_critical,
+        ] {
+            *color = support.adapt(*color);
+        }
+
+        // without colours the banks would be invisible
+        if support == ColorSupport::Monochrome && self.bank_fill == ' ' {
+            self.bank_fill = '#';
+        }
+
+        self.support = support;
+        self
+    }
+
+    /// Blend `color` into the water, `amount` goes from 0 (untouched) to 1.
+    pub fn fade(&self, color: Color, amount: f32) -> Color {
+        let (Some(from), Some(to)) = (to_rgb(color), to_rgb(self.water).or(Some((0, 0, 0)))) else {
+            return color;
+        };
+
+        let amount = amount.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * amount).round() as u8;
+        self.support
+            .adapt(rgb(mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2)))
+    }
+
+    pub fn bank_style(&self) -> ContentStyle {
+        ContentStyle::new().on(self.bank)
+    }
+
+    pub fn water_style(&self) -> ContentStyle {
+        ContentStyle::new().on(self.water)
+    }
+
+    /// A sprite of the given colour, drawn over the water.
+    pub fn on_water(&self, color: Color) -> ContentStyle {
+        ContentStyle::new().with(color).on(self.water)
+    }
+
+    pub fn hud_style(&self) -> ContentStyle {
+        ContentStyle::new().with(self.hud_fg).on(self.hud_bg)
+    }
+
+    /// The colour to paint with, adapted to the terminal
+    pub fn paint(&self, paint: Paint) -> Color {
+        match paint {
+            Paint::Enemy => self.enemy,
+            Paint::Fuel => self.fuel,
+            Paint::Bullet => self.bullet,
+            Paint::Player => self.player,
+            Paint::PowerUp => self.powerup,
+            Paint::Color(color) => self.support.adapt(color),
+        }
+    }
+} // end of Theme implementation.
+
+/// The colour of an entity, a slot of the theme or a colour of its own.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Paint {
+    Enemy,
+    Fuel,
+    Bullet,
+    Player,
+    PowerUp,
+    Color(Color),
+}
+
+impl Paint {
+    /// A slot name, or a colour as [`parse_color`] takes them
+    pub fn parse(value: &str) -> Option<Paint> {
+        match value {
+            "enemy" => Some(Paint::Enemy),
+            "fuel" => Some(Paint::Fuel),
+            "bullet" => Some(Paint::Bullet),
+            "player" => Some(Paint::Player),
+            "powerup" => Some(Paint::PowerUp),
+            value => parse_color(value).map(Paint::Color),
+        }
+    }
+}
+
+/// A theme as written in a file, every key is optional.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    name: Option<String>,
+    base: Option<String>,
+    bank: Option<String>,
+    water: Option<String>,
+    enemy: Option<String>,
+    fuel: Option<String>,
+    bullet: Option<String>,
+    player: Option<String>,
+    powerup: Option<String>,
+    hud_fg: Option<String>,
+    hud_bg: Option<String>,
+    gauge_ok: Option<String>,
+    gauge_low: Option<String>,
+    gauge_critical: Option<String>,
+    bank_fill: Option<char>,
+}
+
+fn rgb(r: u8, g: u8, b: u8) -> Color {
+    Color::Rgb { r, g, b }
+}
+
+/// Accepts `#rrggbb`, an ANSI index (`0`-`255`), `default` or a colour name.
+pub fn parse_color(value: &str) -> Option<Color> {
+    let value = value.trim();
+
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some(rgb(channel(0)?, channel(2)?, channel(4)?));
+    }
+
+    if let Ok(index) = value.parse::<u8>() {
+        return Some(Color::AnsiValue(index));
+    }
+
+    match value {
+        "default" | "reset" => Some(Color::Reset),
+        name => Color::try_from(name).ok(),
+    }
+}
+
+/// The 16 basic colours with their usual xterm values.
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::DarkRed, (128, 0, 0)),
+    (Color::DarkGreen, (0, 128, 0)),
+    (Color::DarkYellow, (128, 128, 0)),
+    (Color::DarkBlue, (0, 0, 128)),
+    (Color::DarkMagenta, (128, 0, 128)),
+    (Color::DarkCyan, (0, 128, 128)),
+    (Color::Grey, (192, 192, 192)),
+    (Color::DarkGrey, (128, 128, 128)),
+    (Color::Red, (255, 0, 0)),
+    (Color::Green, (0, 255, 0)),
+    (Color::Yellow, (255, 255, 0)),
+    (Color::Blue, (0, 0, 255)),
+    (Color::Magenta, (255, 0, 255)),
+    (Color::Cyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+fn to_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Rgb { r, g, b } => Some((r, g, b)),
+        Color::AnsiValue(value) => Some(ansi256_to_rgb(value)),
+        Color::Reset => None,
+        named => ANSI16
+            .iter()
+            .find(|(color, _)| *color == named)
+            .map(|(_, rgb)| *rgb),
+    }
+}
+
+fn ansi256_to_rgb(value: u8) -> (u8, u8, u8) {
+    match value {
+        0..=15 => ANSI16[value as usize].1,
+        16..=231 => {
+            let value = value - 16;
+            (
+                CUBE_LEVELS[(value / 36) as usize],
+                CUBE_LEVELS[(value / 6 % 6) as usize],
+                CUBE_LEVELS[(value % 6) as usize],
+            )
+        }
+        232..=255 => {
+            let grey = 8 + (value - 232) * 10;
+            (grey, grey, grey)
+        }
+    }
+}
+
+fn rgb_to_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    let nearest_level = |c: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| CUBE_LEVELS[i].abs_diff(c))
+            .unwrap() as u8
+    };
+    let cube = 16 + 36 * nearest_level(r) + 6 * nearest_level(g) + nearest_level(b);
+
+    let average = ((r as u16 + g as u16 + b as u16) / 3) as u8;
+    let grey = 232 + (average.saturating_sub(3) / 10).min(23);
+
+    [cube, grey]
+        .into_iter()
+        .min_by_key(|&value| distance((r, g, b), ansi256_to_rgb(value)))
+        .unwrap()
+}
+
+fn rgb_to_ansi16(r: u8, g: u8, b: u8) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, rgb)| distance((r, g, b), *rgb))
+        .map(|(color, _)| *color)
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_colors() {
+        assert_eq!(parse_color("#ff8000"), Some(rgb(255, 128, 0)));
+        assert_eq!(parse_color("22"), Some(Color::AnsiValue(22)));
+        assert_eq!(parse_color("dark_green"), Some(Color::DarkGreen));
+        assert_eq!(parse_color("#ff80"), None);
+        assert_eq!(parse_color("mauve"), None);
+    }
+
+    #[test]
+    fn downgrades_colors() {
+        let orange = rgb(255, 135, 0);
+        assert_eq!(ColorSupport::TrueColor.adapt(orange), orange);
+        assert_eq!(ColorSupport::Ansi256.adapt(orange), Color::AnsiValue(208));
+        assert_eq!(
+            ColorSupport::Ansi256.adapt(rgb(128, 128, 128)),
+            Color::AnsiValue(244)
+        );
+        assert_eq!(ColorSupport::Ansi16.adapt(rgb(250, 10, 10)), Color::Red);
+        assert_eq!(
+            ColorSupport::Ansi16.adapt(Color::AnsiValue(22)),
+            Color::DarkGreen
+        );
+        assert_eq!(ColorSupport::Monochrome.adapt(Color::Blue), Color::Reset);
+    }
+
+    #[test]
+    fn theme_file_overrides_base() {
+        let theme =
+            Theme::from_toml("base = \"mono\"\nname = \"mine\"\nenemy = \"#ff0000\"").unwrap();
+        assert_eq!(theme.name, "mine");
+        assert_eq!(theme.enemy, rgb(255, 0, 0));
+        assert_eq!(theme.bank_fill, '#');
+
+        assert!(Theme::from_toml("water = \"mauve\"").is_err());
+        assert!(Theme::from_toml("lava = \"red\"").is_err());
+    }
+
+    #[test]
+    fn fades_into_the_water() {
+        let theme = Theme::vivid();
+        assert_eq!(theme.fade(theme.enemy, 0.0), theme.enemy);
+        assert_eq!(theme.fade(theme.enemy, 1.0), theme.water);
+
+        let theme = Theme::classic().adapted(ColorSupport::Ansi16);
+        assert_eq!(theme.fade(Color::Yellow, 0.5), Color::DarkGrey);
+        assert_eq!(theme.fade(Color::Yellow, 1.0), Color::Blue);
+    }
+
+    #[test]
+    fn monochrome_banks_stay_visible() {
+        let theme = Theme::classic().adapted(ColorSupport::Monochrome);
+        assert_eq!(theme.bank, Color::Reset);
+        assert_eq!(theme.bank_fill, '#');
+    }
+}
@@ -0,0 +1,274 @@
+//! Swept collisions between what moves during a frame.
+//!
+//! Everything moves by whole cells, a bullet flying 2 lines up while an
+//! enemy scrolls 2 lines down would jump over it. The hits are looked for
+//! along the way instead, in the frame of the ground: the ground stands still
+//! at where it ends up, the bullets and the plane slide through it.
+
+use crate::{
+    drawable::Drawable,
+    entities::{DeathCause, EntityStatus, Location, PlayerStatus},
+    sound::SoundEvent,
+    world::{store::Collider, Effect},
+    World,
+};
+
+impl World {
+    /// Projectiles hit what has health on their way, the earliest hits
+    /// first, each projectile at most once and each target destroyed (and
+    /// scored) once.
+    ///
+    /// The ground scrolled `rows` lines this frame, the projectiles have not
+    /// moved yet.
+    pub(super) fn shoot_down(&mut self, rows: u16) {
+        let (glyphs, theme) = (self.glyphs, &self.theme);
+
+        let mut hits = Vec::new();
+        for projectile in self
+            .entities
+            .alive(|collider| matches!(collider, Collider::Projectile { .. }))
+        {
+            let entity = self.entities.get(projectile);
+            let (Some(sprite), Some(velocity)) = (
+                entity.sprite(glyphs, theme),
+                self.entities.velocity[projectile],
+            ) else {
+                continue;
+            };
+            // where the projectile was, on the ground as it is now
+            let at = entity.location();
+            let from = Location::new(at.c, at.l + rows);
+            let motion = (velocity.c as i32, velocity.l as i32 - rows as i32);
+
+            for (time, target) in self.swept(&sprite, &from, motion) {
+                if self.entities.health[target].is_some() {
+                    hits.push((time, projectile, target));
+                }
+            }
+        }
+        hits.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let multiplier = self.boosts.score_multiplier();
+        for (_, projectile, target) in hits {
+            let entities = &mut self.entities;
+            if entities.status[projectile] != EntityStatus::Alive
+                || entities.status[target] != EntityStatus::Alive
+            {
+                continue;
+            }
+            let Some(Collider::Projectile { damage }) = entities.collider[projectile] else {
+                continue;
+            };
+
+            entities.status[projectile] = EntityStatus::Dead;
+            let health = entities.health[target].as_mut().unwrap();
+            *health = health.saturating_sub(damage);
+            if *health > 0 {
+                continue;
+            }
+
+            entities.status[target] = EntityStatus::DeadBody;
+            let (effect, sound) = match entities.collider[target] {
+                Some(Collider::Depot { .. }) => (Effect::FuelExplosion, SoundEvent::FuelDestroyed),
+                _ => (Effect::EnemyExplosion, SoundEvent::EnemyDestroyed),
+            };
+            self.particles
+                .spawn(effect, &entities.position[target], theme, &mut self.rng);
+            self.sound.emit(sound);
+            let points = entities.score[target]
+                .unwrap_or(0)
+                .saturating_mul(multiplier);
+            self.player.score = self.player.score.saturating_add(points);
+        }
+    }
+
+    /// The plane runs into the hostiles that scrolled through it.
+    pub(super) fn sweep_enemies(&mut self, rows: u16) {
+        if rows == 0 || self.player.status != PlayerStatus::Alive {
+            return;
+        }
+
+        let (glyphs, theme) = (self.glyphs, &self.theme);
+        let player = self.player.sprite(glyphs, theme).unwrap();
+        let location = &self.player.location;
+        let from = Location::new(location.c, location.l + rows);
+        let motion = (0, -(rows as i32));
+        let area = player.swept_bounds(&from, motion);
+
+        // the hostiles met on the way, the first one first
+        loop {
+            let first = self.entities.index.nearest(
+                area.c..area.right(),
+                area.l..area.bottom(),
+                from.l as i32,
+                |entity| {
+                    let hostile = self.entities.get(entity);
+                    self.entities.status[entity] == EntityStatus::Alive
+                        && self.entities.collider[entity] == Some(Collider::Hostile)
+                        && hostile.sprite(glyphs, theme).is_some_and(|sprite| {
+                            player
+                                .sweep(&from, motion, &sprite, hostile.location())
+                                .is_some()
+                        })
+                },
+            );
+            let Some(entity) = first else {
+                return;
+            };
+
+            if self.boosts.absorb() {
+                self.particles.spawn(
+                    Effect::EnemyExplosion,
+                    &self.entities.position[entity],
+                    theme,
+                    &mut self.rng,
+                );
+                self.sound.emit(SoundEvent::ShieldHit);
+                self.entities.status[entity] = EntityStatus::DeadBody;
+            } else {
+                self.player.status = PlayerStatus::Dead(DeathCause::Enemy);
+                return;
+            }
+        }
+    }
+} // end of World implementation.
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::{
+        content::Content,
+        world::{
+            store::{Prototype, Velocity},
+            Weapon,
+        },
+    };
+
+    /// A bullet of the single shot flying `speed` lines and `drift` columns
+    /// a frame
+    fn bullet(world: &World, speed: u16, drift: i16, damage: u16, energy: u16) -> Prototype {
+        Prototype {
+            velocity: Some(Velocity {
+                c: drift,
+                l: -(speed as i16),
+            }),
+            collider: Some(Collider::Projectile { damage }),
+            lifetime: Some(energy),
+            ..world.bullet(Weapon::Single, drift)
+        }
+    }
+
+    #[test]
+    fn fast_bullets_hit_what_they_fly_through() {
+        let mut world = World::new(60, 30);
+        world.speed = 2.0;
+        let (c, l) = (world.player.location.c, world.player.location.l);
+        let enemy = world.entities.spawn(
+            Content::built_in().kind("enemy").unwrap(),
+            Location::new(c, l - 6),
+        );
+        world
+            .entities
+            .spawn(&bullet(&world, 4, 0, 2, 10), Location::new(c, l - 1));
+
+        world.physics();
+        assert_eq!(world.entities.status[enemy], EntityStatus::DeadBody);
+        assert_eq!(world.player.score, 10);
+        world.physics();
+        assert_eq!(world.player.score, 10);
+    }
+
+    #[test]
+    fn the_score_stops_at_its_most() {
+        let mut world = World::new(60, 30);
+        world.player.score = u16::MAX - 5;
+        let (c, l) = (world.player.location.c, world.player.location.l);
+        world.entities.spawn(
+            Content::built_in().kind("enemy").unwrap(),
+            Location::new(c, l - 3),
+        );
+        world
+            .entities
+            .spawn(&bullet(&world, 2, 0, 2, 10), Location::new(c, l - 1));
+
+        world.physics();
+        assert_eq!(world.player.score, u16::MAX);
+    }
+
+    #[test]
+    fn enemies_do_not_scroll_through_the_plane() {
+        let mut world = World::new(60, 30);
+        world.speed = 2.0;
+        let (c, l) = (world.player.location.c, world.player.location.l);
+        // next to the plane after one row, past it after two
+        let enemy = world.entities.spawn(
+            Content::built_in().kind("enemy").unwrap(),
+            Location::new(c, l - 1),
+        );
+        world.sweep_enemies(0);
+        assert_eq!(world.player.status, PlayerStatus::Alive);
+
+        world.entities.move_to(enemy, Location::new(c, l + 1));
+        world.sweep_enemies(2);
+        assert_eq!(world.player.status, PlayerStatus::Dead(DeathCause::Enemy));
+    }
+
+    proptest! {
+        /// A bullet fired at an enemy in its column brings it down, whatever
+        /// the speeds, and scores once.
+        #[test]
+        fn no_bullet_tunnels_through(
+            gap in 1u16..20,
+            bullet_speed in 1u16..=6,
+            speed in prop::sample::select(vec![0.5f32, 1.0, 1.5, 2.0]),
+        ) {
+            let mut world = World::new(60, 30);
+            world.speed = speed;
+            // nothing else on the river
+            world.spawners.clear();
+            let (c, l) = (world.player.location.c, world.player.location.l - 1);
+            world.entities.spawn(Content::built_in().kind("enemy").unwrap(), Location::new(c, l - gap));
+            let bullet = bullet(&world, bullet_speed, 0, 2, 30);
+            world.entities.spawn(&bullet, Location::new(c, l));
+
+            for _ in 0..14 {
+                world.physics();
+            }
+            prop_assert_eq!(world.player.score, 10);
+        }
+
+        /// However the bullets fly, a destroyed target is scored exactly once.
+        #[test]
+        fn one_score_a_destruction(
+            targets in prop::collection::vec((20u16..40, 0u16..25, any::<bool>()), 1..8),
+            bullets in prop::collection::vec((20u16..40, 5u16..29, 1u16..=5, -1i16..=1, 1u16..=2), 1..10),
+            speed in prop::sample::select(vec![0.5f32, 1.0, 2.0]),
+        ) {
+            let mut world = World::new(60, 30);
+            world.speed = speed;
+            world.spawners.clear();
+            let content = Content::built_in();
+            for (c, l, fuel) in targets {
+                let target = content.kind(if fuel { "depot" } else { "enemy" }).unwrap();
+                world.entities.spawn(target, Location::new(c, l));
+            }
+            for (c, l, speed, drift, damage) in bullets {
+                let bullet = bullet(&world, speed, drift, damage, 10);
+                world.entities.spawn(&bullet, Location::new(c, l));
+            }
+
+            let mut destroyed = 0;
+            for _ in 0..8 {
+                world.physics();
+                let entities = &world.entities;
+                destroyed += (0..entities.len())
+                    .filter(|&entity| entities.status[entity] == EntityStatus::DeadBody)
+                    .map(|entity| entities.score[entity].unwrap_or(0) as u32)
+                    .sum::<u32>();
+            }
+            prop_assert_eq!(world.player.score as u32, destroyed);
+        }
+    }
+}
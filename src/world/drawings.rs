@@ -1,159 +1,115 @@
-use std::{
-    io::{Stdout, Write},
-    thread,
-    time::Duration,
-};
-
-use crossterm::{
-    event::{poll, read},
-    style::{ContentStyle, Stylize},
-};
+use crossterm::style::{ContentStyle, Stylize};
 
 use crate::{
-    entities::{DeathCause, PlayerStatus},
-    stout_ext::StdoutExt,
+    canvas::Canvas,
+    entities::PlayerStatus,
+    world::{store::Collider, Controls, Weapon},
     World,
 };
 
 impl World {
-    pub fn clear_screen<'a>(
-        &'a self,
-        stdout: &'a mut Stdout,
-    ) -> Result<&mut Stdout, std::io::Error> {
-        stdout.clear_all()
-    }
-
-    pub(super) fn draw_on_canvas(&mut self) {
-        self.canvas.clear_all();
+    pub fn draw_on_canvas(&self, canvas: &mut Canvas) {
+        canvas.clear_all();
 
         // draw the map
+        let theme = canvas.theme().clone();
+        let bank_fill = theme.bank_fill.to_string();
         for l in 0..self.map.len() {
             let map_c = self.map[l].1;
             let maxc = self.maxc;
-            self.canvas
-                .draw_styled_line((0, l as u16), " ".repeat(self.map[l].0 as usize), ContentStyle::new().on_green())
-                .draw_styled_line((self.map[l].0, l as u16), " ".repeat((self.map[l].1-self.map[l].0) as usize), ContentStyle::new().on_blue())
-                .draw_styled_line((map_c, l as u16), " ".repeat((maxc - map_c) as usize), ContentStyle::new().on_green());
+            canvas
+                .draw_styled_line(
+                    (0, l as u16),
+                    bank_fill.repeat(self.map[l].0 as usize),
+                    theme.bank_style(),
+                )
+                .draw_styled_line(
+                    (self.map[l].0, l as u16),
+                    " ".repeat((self.map[l].1 - self.map[l].0) as usize),
+                    theme.water_style(),
+                )
+                .draw_styled_line(
+                    (map_c, l as u16),
+                    bank_fill.repeat((maxc - map_c) as usize),
+                    theme.bank_style(),
+                );
         }
 
-        let status_style = ContentStyle::new().black().on_white();
-        let gas_present = self.player.gas / 100;
-        let enemies_count = self.enemies.len();
-        self.canvas
+        let status_style = theme.hud_style();
+        let enemies_count = self
+            .entities
+            .alive(|collider| *collider == Collider::Hostile)
+            .count();
+        canvas
             .draw_styled_line(2, format!(" Score: {} ", self.player.score), status_style)
-            .draw_styled_line((2, 3), format!(" Fuel: {} ", gas_present), status_style)
             .draw_styled_line(
                 (2, 4),
                 format!(" Enemies: {} ", enemies_count),
                 status_style,
+            )
+            .draw_styled_line(
+                (2, 5),
+                format!(" Distance: {} ", self.distance),
+                status_style,
             );
-
-        // draw fuel
-        for fuel in self.fuels.iter() {
-            self.canvas.draw(fuel);
+        self.draw_fuel_gauge(canvas, (2, 3));
+        let weapon = match self.player.weapon {
+            Weapon::Single => format!(" Weapon: {} ", Weapon::Single.name()),
+            weapon => format!(" Weapon: {} x{} ", weapon.name(), self.player.ammo),
+        };
+        canvas.draw_styled_line((2, 6), weapon, status_style);
+        let mut line = 7;
+        if self.controls == Controls::Throttle {
+            canvas.draw_styled_line(
+                (2, line),
+                format!(" Speed: {:.1}x ", self.speed),
+                status_style,
+            );
+            line += 1;
         }
+        self.draw_boost_timers(canvas, (2, line));
 
-        // draw enemies
-        for enemy in self.enemies.iter() {
-            self.canvas.draw(enemy);
+        // draw the entities, the ones of the upper layers over the others
+        let mut entities: Vec<_> = (0..self.entities.len()).collect();
+        entities.sort_by_key(|&entity| self.entities.sprite[entity].layer);
+        for entity in entities {
+            canvas.draw(&self.entities.get(entity));
         }
 
-        // draw bullet
-        for bullet in &self.bullets {
-            self.canvas.draw(bullet);
+        // draw the debris, over the bank or the water
+        let glyphs = canvas.glyphs();
+        for particle in self.particles.iter() {
+            let (c, l) = particle.cell();
+            let Some(&(left, right)) = self.map.get(l as usize).filter(|_| l >= 0) else {
+                continue;
+            };
+            if c < 0 || c >= self.maxc as i32 {
+                continue;
+            }
+
+            let background = if c >= left as i32 && c < right as i32 {
+                theme.water
+            } else {
+                theme.bank
+            };
+            let style = ContentStyle::new()
+                .with(particle.color(&theme))
+                .on(background);
+            canvas.acquire_block(c as usize, l as usize, particle.character(glyphs), style);
         }
 
         // draw the player
-        self.canvas.draw(&self.player);
+        if self.player.status == PlayerStatus::Alive {
+            canvas.draw(&self.player);
+        }
     }
 
-    pub(super) fn pause_screen(&mut self) {
-        let pause_msg1: &str = "╔═══════════╗";
-        let pause_msg2: &str = "║Game Paused║";
-        let pause_msg3: &str = "╚═══════════╝";
+    pub fn pause_screen(&self, canvas: &mut Canvas) {
+        let [pause_msg1, pause_msg2, pause_msg3] = canvas.glyphs().pause_box;
 
-        self.canvas
+        canvas
             .draw_line((self.maxc / 2 - 6, self.maxl / 2 - 1), pause_msg1)
             .draw_line((self.maxc / 2 - 6, self.maxl / 2), pause_msg2)
             .draw_line((self.maxc / 2 - 6, self.maxl / 2 + 1), pause_msg3);
     }
-
-    pub fn welcome_screen(&self, stdout: &mut Stdout) -> Result<(), std::io::Error> {
-        let welcome_msg: &str = "██████╗ ██╗██╗   ██╗███████╗██████╗ ██████╗  █████╗ ██╗██████╗     ██████╗ ██╗   ██╗███████╗████████╗\n\r██╔══██╗██║██║   ██║██╔════╝██╔══██╗██╔══██╗██╔══██╗██║██╔══██╗    ██╔══██╗██║   ██║██╔════╝╚══██╔══╝\n\r██████╔╝██║██║   ██║█████╗  ██████╔╝██████╔╝███████║██║██║  ██║    ██████╔╝██║   ██║███████╗   ██║   \n\r██╔══██╗██║╚██╗ ██╔╝██╔══╝  ██╔══██╗██╔══██╗██╔══██║██║██║  ██║    ██╔══██╗██║   ██║╚════██║   ██║   \n\r██║  ██║██║ ╚████╔╝ ███████╗██║  ██║██║  ██║██║  ██║██║██████╔╝    ██║  ██║╚██████╔╝███████║   ██║   \n\r╚═╝  ╚═╝╚═╝  ╚═══╝  ╚══════╝╚═╝  ╚═╝╚═╝  ╚═╝╚═╝  ╚═╝╚═╝╚═════╝     ╚═╝  ╚═╝ ╚═════╝ ╚══════╝   ╚═╝   \n";
-        self.clear_screen(stdout)?;
-
-        if self.maxc > 100 {
-            stdout.draw((0, 2), welcome_msg)?;
-        } else {
-            stdout.draw((0, 2), "RiverRaid Rust")?;
-        }
-
-        stdout.draw((2, self.maxl - 2), "Press any key to continue...")?;
-        stdout.flush()?;
-
-        loop {
-            if poll(Duration::from_millis(0)).unwrap() {
-                read()?;
-                break;
-            }
-        }
-        self.clear_screen(stdout)?;
-
-        Ok(())
-    }
-
-    pub fn goodbye_screen(&self, stdout: &mut Stdout) -> Result<(), std::io::Error> {
-        let goodbye_msg1: &str = " ██████╗  ██████╗  ██████╗ ██████╗      ██████╗  █████╗ ███╗   ███╗███████╗██╗\n\r██╔════╝ ██╔═══██╗██╔═══██╗██╔══██╗    ██╔════╝ ██╔══██╗████╗ ████║██╔════╝██║\n\r██║  ███╗██║   ██║██║   ██║██║  ██║    ██║  ███╗███████║██╔████╔██║█████╗  ██║\n\r██║   ██║██║   ██║██║   ██║██║  ██║    ██║   ██║██╔══██║██║╚██╔╝██║██╔══╝  ╚═╝\n\r╚██████╔╝╚██████╔╝╚██████╔╝██████╔╝    ╚██████╔╝██║  ██║██║ ╚═╝ ██║███████╗██╗\n\r ╚═════╝  ╚═════╝  ╚═════╝ ╚═════╝      ╚═════╝ ╚═╝  ╚═╝╚═╝     ╚═╝╚══════╝╚═╝\n";
-        let goodbye_msg2: &str = "████████╗██╗  ██╗ █████╗ ███╗   ██╗██╗  ██╗███████╗\n\r╚══██╔══╝██║  ██║██╔══██╗████╗  ██║██║ ██╔╝██╔════╝\n\r   ██║   ███████║███████║██╔██╗ ██║█████╔╝ ███████╗\n\r   ██║   ██╔══██║██╔══██║██║╚██╗██║██╔═██╗ ╚════██║\n\r   ██║   ██║  ██║██║  ██║██║ ╚████║██║  ██╗███████║██╗\n\r   ╚═╝   ╚═╝  ╚═╝╚═╝  ╚═╝╚═╝  ╚═══╝╚═╝  ╚═╝╚══════╝╚═╝\n";
-
-        self.clear_screen(stdout)?
-            .draw((0, 2), goodbye_msg1)?
-            .draw((0, 10), goodbye_msg2)?;
-
-        stdout.move_cursor((2, self.maxl - 5))?;
-        if let PlayerStatus::Dead(cause) = &self.player.status {
-            match cause {
-                DeathCause::Ground => {
-                    if self.maxc > 91 {
-                        stdout.print("\r█▄█ █▀█ █░█   █▀▀ █▀█ ▄▀█ █▀ █░█ █▀▀ █▀▄   █ █▄░█   ▀█▀ █░█ █▀▀   █▀▀ █▀█ █▀█ █░█ █▄░█ █▀▄ ░\n\r░█░ █▄█ █▄█   █▄▄ █▀▄ █▀█ ▄█ █▀█ ██▄ █▄▀   █ █░▀█   ░█░ █▀█ ██▄   █▄█ █▀▄ █▄█ █▄█ █░▀█ █▄▀ ▄\n\r")?;
-                    } else {
-                        stdout.print("You crashed in the ground.")?;
-                    }
-                }
-                DeathCause::Enemy => {
-                    if self.maxc > 72 {
-                        stdout.print("\r▄▀█ █▄░█   █▀▀ █▄░█ █▀▀ █▀▄▀█ █▄█   █▄▀ █ █░░ █░░ █▀▀ █▀▄   █▄█ █▀█ █░█ ░\n\r█▀█ █░▀█   ██▄ █░▀█ ██▄ █░▀░█ ░█░   █░█ █ █▄▄ █▄▄ ██▄ █▄▀   ░█░ █▄█ █▄█ ▄\n\r")?;
-                    } else {
-                        stdout.print("An enemy killed you.")?;
-                    }
-                }
-                DeathCause::Fuel => {
-                    if self.maxc > 69 {
-                        stdout.print("\r█▄█ █▀█ █░█   █▀█ ▄▀█ █▄░█   █▀█ █░█ ▀█▀   █▀█ █▀▀   █▀▀ █░█ █▀▀ █░░ ░\n\r░█░ █▄█ █▄█   █▀▄ █▀█ █░▀█   █▄█ █▄█ ░█░   █▄█ █▀░   █▀░ █▄█ ██▄ █▄▄ ▄\n\r")?;
-                    } else {
-                        stdout.print("You ran out of fuel.")?;
-                    }
-                }
-            }
-        } else {
-            // Quit
-            if self.player.status != PlayerStatus::Quit {
-                unreachable!("Undead player has no death cause!")
-            }
-        }
-
-        stdout.move_cursor((2, self.maxl - 2))?;
-        thread::sleep(Duration::from_millis(2000));
-        stdout.print("Press any key to continue...")?;
-        stdout.flush()?;
-        loop {
-            if poll(Duration::from_millis(0)).unwrap() {
-                read()?;
-                break;
-            }
-        }
-
-        self.clear_screen(stdout)?;
-        Ok(())
-    }
 }
@@ -0,0 +1,200 @@
+//! The fuel tank: burning, refuelling and the gauge in the HUD.
+//!
+//! Depots are filled with the `gas` of their kind in the content pack and
+//! give it away a little every frame the plane spends over them, see
+//! `contacts` in `physics.rs`.
+
+use crossterm::style::{Attribute, Color, ContentStyle, Stylize};
+
+use crate::{canvas::Canvas, sound::SoundEvent, World};
+
+/// Gas in a full tank
+pub const FULL_TANK: u16 = 1700;
+
+/// Gas a depot gives each frame the plane is over it
+pub(super) const REFUEL_RATE: u16 = 20;
+
+/// Cells of the gauge
+const GAUGE_WIDTH: usize = 10;
+
+/// Frames the low fuel warning is shown, then hidden
+const BLINK: u32 = 8;
+
+/// When to warn that the tank runs dry.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FuelWarning {
+    /// Percent of the tank
+    pub level: u8,
+    /// Ring (or play the low fuel sound) every 100 units below the level
+    pub bell: bool,
+}
+
+impl Default for FuelWarning {
+    fn default() -> Self {
+        FuelWarning {
+            level: 25,
+            bell: true,
+        }
+    }
+}
+
+/// A `width` cells long bar, filled to `fraction` with the partial `steps`.
+pub(super) fn gauge(fraction: f32, width: usize, steps: &[char]) -> String {
+    let units = (fraction.clamp(0.0, 1.0) * (width * steps.len()) as f32).round() as usize;
+    let (full, part) = (units / steps.len(), units % steps.len());
+
+    let mut bar: String = steps[steps.len() - 1].to_string().repeat(full);
+    if part > 0 {
+        bar.push(steps[part - 1]);
+    }
+    format!("{bar:width$}")
+}
+
+impl World {
+    /// Below the warning level
+    pub fn low_on_fuel(&self) -> bool {
+        self.player.gas as u32 * 100 <= self.player.max_gas as u32 * self.fuel_warning.level as u32
+    }
+
+    /// Fill the tank, never past full
+    pub(super) fn refuel(&mut self, amount: u16) {
+        self.player.gas = self
+            .player
+            .gas
+            .saturating_add(amount)
+            .min(self.player.max_gas);
+    }
+
+    pub(super) fn burn_fuel(&mut self) {
+        if self.player.gas >= 1 {
+            self.player.gas -= 1;
+
+            if self.fuel_warning.bell && self.low_on_fuel() && self.player.gas.is_multiple_of(100) {
+                self.sound.emit(SoundEvent::LowFuel);
+            }
+        }
+    }
+
+    pub(super) fn draw_fuel_gauge(&self, canvas: &mut Canvas, (c, l): (u16, u16)) {
+        let theme = canvas.theme().clone();
+        let fraction = self.player.gas as f32 / self.player.max_gas as f32;
+        let color = if self.low_on_fuel() {
+            theme.gauge_critical
+        } else if fraction <= 0.5 {
+            theme.gauge_low
+        } else {
+            theme.gauge_ok
+        };
+
+        let bar = gauge(fraction, GAUGE_WIDTH, canvas.glyphs().gauge);
+        let bar_style = ContentStyle::new().with(color).on(theme.hud_fg);
+        let end = c + 6 + GAUGE_WIDTH as u16;
+        canvas
+            .draw_styled_line((c, l), " Fuel ", theme.hud_style())
+            .draw_styled_line((c + 6, l), bar, bar_style)
+            .draw_styled_line((end, l), " ", theme.hud_style());
+
+        if self.refueling {
+            let refueling = ContentStyle::new().with(theme.hud_bg).on(theme.hud_fg);
+            canvas.draw_styled_line((end + 2, l), " REFUELING ", refueling);
+        } else if self.low_on_fuel() && (self.frames / BLINK).is_multiple_of(2) {
+            let warning = ContentStyle::new()
+                .with(theme.hud_bg)
+                .on(match color {
+                    Color::Reset => theme.hud_fg,
+                    color => color,
+                })
+                .attribute(Attribute::Bold);
+            canvas.draw_styled_line((end + 2, l), " LOW FUEL ", warning);
+        }
+    }
+} // end of World implementation.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        content::Content,
+        entities::{EntityStatus, Location},
+        glyphs::{ASCII, UNICODE},
+        world::{store::Collider, Weapon},
+    };
+
+    #[test]
+    fn draws_partial_gauges() {
+        assert_eq!(gauge(1.0, 4, UNICODE.gauge), "████");
+        assert_eq!(gauge(0.5, 3, UNICODE.gauge), "█▌ ");
+        assert_eq!(gauge(0.0, 3, UNICODE.gauge), "   ");
+        assert_eq!(gauge(0.3, 5, ASCII.gauge), "=-   ");
+    }
+
+    #[test]
+    fn the_tank_has_a_limit() {
+        let mut world = World::new(40, 20);
+        world.player.gas = FULL_TANK - 50;
+        world.refuel(200);
+        assert_eq!(world.player.gas, FULL_TANK);
+    }
+
+    #[test]
+    fn warns_below_the_level() {
+        let mut world = World::new(40, 20);
+        world.fuel_warning.level = 50;
+        world.player.gas = FULL_TANK / 2 + 1;
+        assert!(!world.low_on_fuel());
+
+        world.burn_fuel();
+        assert!(world.low_on_fuel());
+        let mut canvas = Canvas::new(40, 20);
+        world.player.gas = 800;
+        world.draw_fuel_gauge(&mut canvas, (2, 3));
+        assert!(canvas.text().contains("LOW FUEL"));
+    }
+
+    #[test]
+    fn the_warning_blinks_with_the_frames() {
+        let mut world = World::new(40, 20);
+        // the gas stays put, only the frames go by
+        world.player.gas = 100;
+        let mut shown = |frames| {
+            world.frames = frames;
+            let mut canvas = Canvas::new(40, 20);
+            world.draw_fuel_gauge(&mut canvas, (2, 3));
+            canvas.text().contains("LOW FUEL")
+        };
+        assert!(shown(0) && shown(BLINK - 1));
+        assert!(!shown(BLINK) && !shown(2 * BLINK - 1));
+        assert!(shown(2 * BLINK));
+    }
+
+    #[test]
+    fn depots_refuel_over_time_until_shot() {
+        let mut world = World::new(40, 20);
+        world.player.gas = 1000;
+        let (c, l) = (world.player.location.c, world.player.location.l);
+        let kind = Content::built_in().kind("depot").unwrap();
+        let Some(Collider::Depot { gas: capacity }) = kind.collider else {
+            panic!("depots have gas");
+        };
+        let depot = world.entities.spawn(kind, Location::new(c, l - 3));
+
+        world.physics();
+        assert!(world.refueling);
+        assert_eq!(world.player.gas, 1000 + REFUEL_RATE - 1);
+        assert_eq!(
+            world.entities.collider[depot],
+            Some(Collider::Depot {
+                gas: capacity - REFUEL_RATE
+            })
+        );
+
+        // shot while refuelling, the depot is gone
+        let bullet = world.bullet(Weapon::Single, 0);
+        world.entities.spawn(&bullet, Location::new(c, l - 1));
+        world.physics();
+        assert_eq!(world.entities.status[depot], EntityStatus::DeadBody);
+        world.physics();
+        assert!(!world.refueling);
+        assert_eq!(world.player.gas, 1000 + 2 * REFUEL_RATE - 3);
+    }
+}
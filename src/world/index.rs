@@ -0,0 +1,226 @@
+//! Where the entities are, bucketed by line, for the collision queries.
+//!
+//! The index keeps the entities of the store by number, and the store keeps
+//! it up to date: `Store::spawn`, `Store::move_to` and `Store::bury` each
+//! touch the lines of their own entities only.
+
+use std::ops::Range;
+
+use crate::{
+    drawable::Drawable,
+    entities::Location,
+    sprite::{Rect, Sprite},
+    world::store::Entity,
+    World,
+};
+
+#[cfg(test)]
+use crate::world::store::Store;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Entry {
+    pub entity: Entity,
+    pub area: Rect,
+}
+
+/// The entities on each line, from the top of the screen down. Entities
+/// below the screen are kept until the plane is done with them.
+#[derive(Default)]
+pub struct SpatialIndex {
+    rows: Vec<Vec<Entry>>,
+}
+
+impl SpatialIndex {
+    /// The rows of `lines` that are indexed
+    fn rows(&self, lines: Range<i32>) -> Range<usize> {
+        let clamp = |l: i32| l.clamp(0, self.rows.len() as i32) as usize;
+        clamp(lines.start)..clamp(lines.end)
+    }
+
+    /// Add an entity to every line it covers; nothing of it above the screen.
+    pub fn insert(&mut self, entity: Entity, area: Rect) {
+        let bottom = area.bottom().max(0) as usize;
+        if self.rows.len() < bottom {
+            self.rows.resize_with(bottom, Vec::new);
+        }
+        for l in self.rows(area.l..area.bottom()) {
+            self.rows[l].push(Entry { entity, area });
+        }
+    }
+
+    /// Take an entity out of the lines of `area`, where it was inserted.
+    pub fn remove(&mut self, entity: Entity, area: Rect) {
+        for l in self.rows(area.l..area.bottom()) {
+            self.rows[l].retain(|entry| entry.entity != entity);
+        }
+    }
+
+    /// The entity `from`, inserted with `area`, is numbered `to` now.
+    pub fn renumber(&mut self, from: Entity, to: Entity, area: Rect) {
+        for l in self.rows(area.l..area.bottom()) {
+            for entry in self.rows[l].iter_mut().filter(|entry| entry.entity == from) {
+                entry.entity = to;
+            }
+        }
+    }
+
+    /// The entities overlapping `area`, each of them once.
+    pub fn overlapping(&self, area: Rect) -> impl Iterator<Item = Entry> + '_ {
+        self.rows(area.l..area.bottom()).flat_map(move |l| {
+            self.rows[l]
+                .iter()
+                // an entity on several lines is taken from the first one asked
+                .filter(move |entry| {
+                    entry.area.overlaps(&area) && entry.area.l.max(area.l).max(0) == l as i32
+                })
+                .copied()
+        })
+    }
+
+    /// The entity in `columns` and `lines` nearest to the line `from`,
+    /// among the ones `accept` takes.
+    pub fn nearest(
+        &self,
+        columns: Range<i32>,
+        lines: Range<i32>,
+        from: i32,
+        accept: impl Fn(Entity) -> bool,
+    ) -> Option<Entity> {
+        let area = Rect::new(
+            columns.start,
+            lines.start,
+            columns.end - columns.start,
+            lines.end - lines.start,
+        );
+        let distance = |area: &Rect| {
+            if from < area.l {
+                area.l - from
+            } else {
+                (from - (area.bottom() - 1)).max(0)
+            }
+        };
+
+        self.overlapping(area)
+            .filter(|entry| accept(entry.entity))
+            .min_by_key(|entry| distance(&entry.area))
+            .map(|entry| entry.entity)
+    }
+} // end of SpatialIndex implementation.
+
+#[cfg(test)]
+impl Store {
+    /// The index kept up to date is the one built from scratch.
+    pub fn check_index(&self) {
+        let mut index = SpatialIndex::default();
+        for entity in 0..self.len() {
+            index.insert(entity, self.area(entity, &self.position[entity]));
+        }
+
+        let lines = |index: &SpatialIndex| {
+            let mut rows = index.rows.clone();
+            for row in rows.iter_mut() {
+                row.sort_by_key(|entry| entry.entity);
+            }
+            while rows.last().is_some_and(Vec::is_empty) {
+                rows.pop();
+            }
+            rows
+        };
+        assert_eq!(lines(&self.index), lines(&index));
+    }
+}
+
+impl World {
+    /// The entities sharing a cell with `sprite` at `at`.
+    pub(super) fn touching(&self, sprite: &Sprite, at: &Location) -> Vec<Entity> {
+        self.entities
+            .index
+            .overlapping(sprite.bounds(at))
+            .filter(|entry| {
+                let entity = self.entities.get(entry.entity);
+                entity
+                    .sprite(self.glyphs, &self.theme)
+                    .is_some_and(|other| sprite.overlaps(at, &other, entity.location()))
+            })
+            .map(|entry| entry.entity)
+            .collect()
+    }
+
+    /// The entities that `sprite` meets sliding from `from` by `motion`,
+    /// with when it meets them, see [`Sprite::sweep`].
+    pub(super) fn swept(
+        &self,
+        sprite: &Sprite,
+        from: &Location,
+        motion: (i32, i32),
+    ) -> Vec<(f32, Entity)> {
+        self.entities
+            .index
+            .overlapping(sprite.swept_bounds(from, motion))
+            .filter_map(|entry| {
+                let entity = self.entities.get(entry.entity);
+                let other = entity.sprite(self.glyphs, &self.theme)?;
+                let time = sprite.sweep(from, motion, &other, entity.location())?;
+                Some((time, entry.entity))
+            })
+            .collect()
+    }
+} // end of World implementation.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tall_entities_are_found_once() {
+        let mut index = SpatialIndex::default();
+        index.insert(0, Rect::new(4, -1, 3, 4));
+        index.insert(1, Rect::new(5, 6, 1, 1));
+        index.insert(2, Rect::new(9, 2, 1, 1));
+
+        let found: Vec<Entry> = index.overlapping(Rect::new(0, 0, 8, 10)).collect();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].entity, 0);
+        assert_eq!(index.overlapping(Rect::new(6, 2, 1, 1)).count(), 1);
+        assert_eq!(index.overlapping(Rect::new(7, 0, 2, 10)).count(), 0);
+    }
+
+    #[test]
+    fn nearest_enemy_in_the_columns() {
+        let mut index = SpatialIndex::default();
+        for (i, (c, l)) in [(3, 2), (4, 12), (5, 9), (9, 10)].into_iter().enumerate() {
+            index.insert(i, Rect::new(c, l, 1, 1));
+        }
+        // a depot, that is not asked for
+        index.insert(4, Rect::new(4, 10, 3, 4));
+        let enemy = |i| i != 4;
+
+        assert_eq!(index.nearest(3..6, 0..20, 11, enemy), Some(1));
+        assert_eq!(index.nearest(3..6, 0..11, 11, enemy), Some(2));
+        assert_eq!(
+            index.nearest(3..6, 0..11, 11, |i| enemy(i) && i != 2),
+            Some(0)
+        );
+        assert_eq!(index.nearest(6..9, 0..20, 11, enemy), None);
+    }
+
+    #[test]
+    fn entities_leave_and_are_renumbered_in_their_lines() {
+        let mut index = SpatialIndex::default();
+        let (tall, small) = (Rect::new(4, 1, 3, 4), Rect::new(5, 6, 1, 1));
+        index.insert(0, tall);
+        index.insert(1, small);
+
+        index.remove(0, tall);
+        index.renumber(1, 0, small);
+        assert_eq!(index.overlapping(Rect::new(0, 0, 10, 5)).count(), 0);
+        let found: Vec<Entry> = index.overlapping(Rect::new(0, 0, 10, 10)).collect();
+        assert_eq!(
+            found,
+            [Entry {
+                entity: 0,
+                area: small
+            }]
+        );
+    }
+}
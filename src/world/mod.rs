@@ -1,23 +1,48 @@
-use std::{collections::VecDeque, io::Stdout, thread, time::Duration};
+use std::collections::VecDeque;
 
 use rand::{rngs::ThreadRng, thread_rng};
 
 use crate::{
-    canvas::Canvas,
-    entities::{Bullet, Enemy, Fuel, Location, Player, PlayerStatus},
-    handle_pressed_keys,
+    content::Content,
+    entities::{Location, Player, PlayerStatus},
+    glyphs::{GlyphSet, UNICODE},
+    level::Level,
+    sound::SoundBus,
+    theme::Theme,
 };
 
+mod collision;
 mod drawings;
+mod fuel;
+mod index;
+mod movement;
+mod particles;
 mod physics;
+mod powerups;
+pub mod prototypes;
+mod script;
+pub mod store;
+mod throttle;
+mod weapons;
 
+pub use fuel::FuelWarning;
+pub use movement::Movement;
+pub use particles::{Effect, Particles};
+pub use powerups::{Boost, Boosts};
+pub use throttle::Controls;
+pub use weapons::Weapon;
+
+use prototypes::Spawner;
+use script::Script;
+use store::{Look, Store};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum WorldStatus {
     Fluent,
     Paused,
 }
 
 pub struct World {
-    canvas: Canvas,
     pub status: WorldStatus,
     pub player: Player,
     pub map: VecDeque<(u16, u16)>,
@@ -25,50 +50,146 @@ pub struct World {
     pub maxl: u16,
     pub next_right: u16,
     pub next_left: u16,
-    pub enemies: Vec<Enemy>,
-    pub fuels: Vec<Fuel>,
-    pub bullets: Vec<Bullet>,
+    /// Everything on the river but the plane
+    pub entities: Store,
+    /// What shows up on each new row
+    pub spawners: Vec<Spawner>,
+    /// The level played, until it hands over to the random river
+    script: Option<Script>,
+    /// How the shots look
+    bullet: Look,
+    /// The boosts running, picked from power-ups
+    pub boosts: Boosts,
+    pub particles: Particles,
+    pub sound: SoundBus,
+    pub movement: Movement,
+    pub controls: Controls,
+    pub fuel_warning: FuelWarning,
+    /// The plane is over a depot and filling up
+    pub refueling: bool,
+    /// Rows scrolled a frame
+    pub speed: f32,
+    /// The part of a row scrolled, but not drawn yet
+    scrolled: f32,
+    /// Rows flown since the start
+    pub distance: u32,
+    /// Frames since the start, paused or not, for what blinks
+    pub frames: u32,
     pub rng: ThreadRng, // Local rng for the whole world
+    // collisions use the sprites the world is drawn with
+    theme: Theme,
+    glyphs: &'static GlyphSet,
 }
 
 impl World {
     pub fn new(maxc: u16, maxl: u16) -> World {
         World {
             status: WorldStatus::Fluent,
-            canvas: Canvas::new(maxc, maxl),
             player: Player {
                 location: Location::new(maxc / 2, maxl - 1),
                 status: PlayerStatus::Alive,
                 score: 0,
-                gas: 1700,
+                gas: fuel::FULL_TANK,
+                max_gas: fuel::FULL_TANK,
+                weapon: Weapon::Single,
+                ammo: 0,
+                cooldown: 0,
+                steering: (0, 0),
+                velocity: (0.0, 0.0),
+                offset: (0.0, 0.0),
             },
             map: VecDeque::from(vec![(maxc / 2 - 5, maxc / 2 + 5); maxl as usize]),
             maxc,
             maxl,
             next_left: maxc / 2 - 7,
             next_right: maxc / 2 + 7,
-            enemies: Vec::new(),
-            bullets: Vec::new(),
-            fuels: Vec::new(),
+            entities: Store::default(),
+            spawners: Content::built_in().spawners.clone(),
+            script: None,
+            bullet: Content::built_in().bullet(),
+            boosts: Boosts::default(),
+            particles: Particles::default(),
+            sound: SoundBus::muted(),
+            movement: Movement::default(),
+            controls: Controls::Free,
+            fuel_warning: FuelWarning::default(),
+            refueling: false,
+            speed: throttle::CRUISE,
+            scrolled: 0.0,
+            distance: 0,
+            frames: 0,
             rng: thread_rng(),
+            theme: Theme::default(),
+            glyphs: &UNICODE,
         }
     }
 
-    pub fn game_loop(&mut self, stdout: &mut Stdout, slowness: u64) -> Result<(), std::io::Error> {
-        while self.player.status == PlayerStatus::Alive {
-            handle_pressed_keys(self);
-            match self.status {
-                WorldStatus::Fluent => {
-                    self.physics();
-                    self.draw_on_canvas();
-                }
-                WorldStatus::Paused => self.pause_screen(),
-            }
+    pub fn with_theme(mut self, theme: Theme) -> World {
+        self.theme = theme;
+        self
+    }
+
+    pub fn with_glyphs(mut self, glyphs: &'static GlyphSet) -> World {
+        self.glyphs = glyphs;
+        // nothing is on the river yet, it is indexed with the new sprites
+        debug_assert_eq!(self.entities.len(), 0);
+        self.entities = Store::new(glyphs);
+        self
+    }
+
+    pub fn with_content(mut self, content: &Content) -> World {
+        self.spawners = content.spawners.clone();
+        self.bullet = content.bullet();
+        self
+    }
+
+    /// Play `level` instead of the random river, from its row `from`
+    pub fn with_level(mut self, level: &Level, content: &Content, from: u32) -> World {
+        let (left, right) = level.banks(from, self.maxc);
+        self.map = VecDeque::from(vec![(left, right); self.maxl as usize]);
+        (self.next_left, self.next_right) = (left, right);
+        self.player.location.c = (left + right) / 2;
+        self.script = Some(Script::new(level, content, from));
+        self
+    }
 
-            self.canvas.draw_map(stdout)?;
-            thread::sleep(Duration::from_millis(slowness));
+    pub fn with_sound(mut self, sound: SoundBus) -> World {
+        self.sound = sound;
+        self
+    }
+
+    pub fn with_movement(mut self, movement: Movement) -> World {
+        self.movement = movement;
+        self
+    }
+
+    pub fn with_controls(mut self, controls: Controls) -> World {
+        self.controls = controls;
+        self
+    }
+
+    pub fn with_fuel_warning(mut self, fuel_warning: FuelWarning) -> World {
+        self.fuel_warning = fuel_warning;
+        self
+    }
+
+    /// Advance the world by one frame, unless it is paused
+    pub fn tick(&mut self) {
+        self.frames = self.frames.wrapping_add(1);
+        if let WorldStatus::Fluent = self.status {
+            if self.player.status == PlayerStatus::Alive {
+                self.physics();
+            } else {
+                // let the wreck burn out, the river stopped
+                self.particles.update(0);
+            }
+            self.sound.flush();
         }
+    }
 
-        Ok(())
+    /// The game is over once the player is gone and the debris settled
+    pub fn is_over(&self) -> bool {
+        self.player.status != PlayerStatus::Alive
+            && (self.player.status == PlayerStatus::Quit || self.particles.is_empty())
     }
 } // end of World implementation.
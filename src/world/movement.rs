@@ -0,0 +1,146 @@
+//! How the plane answers the controls.
+//!
+//! The input only says which directions are held, in `Player::steering`;
+//! the plane is moved here, at the start of every frame of physics.
+
+use crate::{entities::Player, world::Controls, World};
+
+/// Below this speed a drifting plane stops
+const STILL: f32 = 0.05;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Movement {
+    /// Speed up and slow down, or the classic one cell a frame
+    pub analog: bool,
+    /// Cells a frame, in analog movement
+    pub max_speed: f32,
+    /// How much of its speed the plane keeps every frame, from 0 to below 1
+    pub inertia: f32,
+}
+
+impl Default for Movement {
+    fn default() -> Self {
+        Movement {
+            analog: false,
+            max_speed: 1.5,
+            inertia: 0.6,
+        }
+    }
+}
+
+impl Movement {
+    pub fn name(&self) -> &'static str {
+        if self.analog {
+            "analog"
+        } else {
+            "classic"
+        }
+    }
+
+    /// The velocity of the next frame on one axis, `steering` from -1 to 1.
+    fn accelerate(&self, velocity: f32, steering: i8) -> f32 {
+        if !self.analog {
+            return steering as f32;
+        }
+
+        let velocity =
+            velocity * self.inertia + steering as f32 * self.max_speed * (1.0 - self.inertia);
+        if steering == 0 && velocity.abs() < STILL {
+            0.0
+        } else {
+            velocity
+        }
+    }
+}
+
+/// Move `at` by the whole cells of `offset` and keep the rest for later.
+/// The plane stops against the edges of `1..=max`.
+fn step(at: u16, offset: &mut f32, velocity: &mut f32, max: u16) -> u16 {
+    let whole = offset.trunc();
+    *offset -= whole;
+
+    let to = at as f32 + whole;
+    if to < 1.0 || to > max as f32 {
+        *offset = 0.0;
+        *velocity = 0.0;
+    }
+    to.clamp(1.0, max as f32) as u16
+}
+
+impl World {
+    pub(super) fn move_player(&mut self) {
+        let Player {
+            location,
+            steering,
+            velocity,
+            offset,
+            ..
+        } = &mut self.player;
+
+        velocity.0 = self.movement.accelerate(velocity.0, steering.0);
+        // with the throttle, up and down are for the speed
+        let vertical = if self.controls == Controls::Throttle {
+            0
+        } else {
+            steering.1
+        };
+        velocity.1 = self.movement.accelerate(velocity.1, vertical);
+        offset.0 += velocity.0;
+        offset.1 += velocity.1;
+
+        location.c = step(location.c, &mut offset.0, &mut velocity.0, self.maxc - 1);
+        location.l = step(location.l, &mut offset.1, &mut velocity.1, self.maxl - 1);
+    }
+} // end of World implementation.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fly(world: &mut World, steering: (i8, i8), frames: usize) -> Vec<u16> {
+        world.player.steering = steering;
+        (0..frames)
+            .map(|_| {
+                world.move_player();
+                world.player.location.c
+            })
+            .collect()
+    }
+
+    #[test]
+    fn classic_moves_one_cell_a_frame() {
+        let mut world = World::new(40, 20);
+        assert_eq!(fly(&mut world, (1, 0), 3), [21, 22, 23]);
+        assert_eq!(fly(&mut world, (0, 0), 2), [23, 23]);
+    }
+
+    #[test]
+    fn analog_speeds_up_and_drifts() {
+        let mut world = World::new(40, 20).with_movement(Movement {
+            analog: true,
+            ..Default::default()
+        });
+        let speeds: Vec<f32> = (0..6)
+            .map(|_| {
+                fly(&mut world, (-1, 0), 1);
+                -world.player.velocity.0
+            })
+            .collect();
+        assert!(speeds.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(speeds[0] < 1.0 && speeds[5] > 1.0 && speeds[5] <= 1.5);
+        let before = world.player.location.c;
+
+        // let go, the plane keeps going for a bit, then stops
+        let stopping = fly(&mut world, (0, 0), 20);
+        assert!(stopping[0] < before);
+        assert_eq!(world.player.velocity.0, 0.0);
+    }
+
+    #[test]
+    fn stops_against_the_edges() {
+        let mut world = World::new(40, 20);
+        fly(&mut world, (0, 1), 10);
+        assert_eq!(world.player.location.l, 19);
+        assert_eq!(world.player.velocity.1, 0.0);
+    }
+}
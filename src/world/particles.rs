@@ -0,0 +1,188 @@
+//! Debris thrown around when something blows up.
+//!
+//! Particles are only decoration: they live next to the entities but are
+//! never looked at by the collision checks in `physics.rs`.
+
+use crossterm::style::Color;
+use rand::Rng;
+
+use crate::{entities::Location, glyphs::GlyphSet, theme::Theme};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Effect {
+    EnemyExplosion,
+    FuelExplosion,
+    PlayerCrash,
+}
+
+/// What an effect looks like.
+struct Recipe {
+    count: usize,
+    speed: (f32, f32),
+    lifetime: (u16, u16),
+    /// Carried down with the ground by the river, like the wreck it came from
+    floats: bool,
+}
+
+impl Effect {
+    fn recipe(self) -> Recipe {
+        match self {
+            Effect::EnemyExplosion => Recipe {
+                count: 8,
+                speed: (0.4, 1.0),
+                lifetime: (5, 9),
+                floats: true,
+            },
+            Effect::FuelExplosion => Recipe {
+                count: 12,
+                speed: (0.2, 0.6),
+                lifetime: (8, 14),
+                floats: true,
+            },
+            Effect::PlayerCrash => Recipe {
+                count: 20,
+                speed: (0.3, 1.4),
+                lifetime: (10, 20),
+                floats: false,
+            },
+        }
+    }
+
+    fn color(self, theme: &Theme, index: usize) -> Color {
+        match self {
+            Effect::EnemyExplosion => theme.enemy,
+            Effect::FuelExplosion => theme.fuel,
+            // fire and pieces of the plane
+            Effect::PlayerCrash => match index % 3 {
+                0 => theme.player,
+                1 => theme.fuel,
+                _ => theme.enemy,
+            },
+        }
+    }
+}
+
+pub struct Particle {
+    c: f32,
+    l: f32,
+    velocity: (f32, f32),
+    age: u16,
+    lifetime: u16,
+    color: Color,
+    floats: bool,
+}
+
+impl Particle {
+    /// The screen cell of the particle, may be outside of the screen.
+    pub fn cell(&self) -> (i32, i32) {
+        (self.c.round() as i32, self.l.round() as i32)
+    }
+
+    /// From 0 when spawned to 1 when about to disappear.
+    fn progress(&self) -> f32 {
+        self.age as f32 / self.lifetime as f32
+    }
+
+    pub fn character(&self, glyphs: &GlyphSet) -> char {
+        let step = (self.progress() * glyphs.debris.len() as f32) as usize;
+        glyphs.debris[step.min(glyphs.debris.len() - 1)]
+    }
+
+    pub fn color(&self, theme: &Theme) -> Color {
+        theme.fade(self.color, self.progress())
+    }
+}
+
+#[derive(Default)]
+pub struct Particles {
+    particles: Vec<Particle>,
+}
+
+impl Particles {
+    pub fn spawn(&mut self, effect: Effect, at: &Location, theme: &Theme, rng: &mut impl Rng) {
+        let recipe = effect.recipe();
+
+        for index in 0..recipe.count {
+            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            let speed = rng.gen_range(recipe.speed.0..recipe.speed.1);
+            self.particles.push(Particle {
+                c: at.c as f32,
+                l: at.l as f32,
+                // cells are about twice as high as wide
+                velocity: (angle.cos() * speed * 2.0, angle.sin() * speed),
+                age: 0,
+                lifetime: rng.gen_range(recipe.lifetime.0..=recipe.lifetime.1),
+                color: effect.color(theme, index),
+                floats: recipe.floats,
+            });
+        }
+    }
+
+    /// Move the particles, the river scrolled `rows` lines this frame, and
+    /// forget the expired ones.
+    pub fn update(&mut self, rows: u16) {
+        self.particles.retain_mut(|particle| {
+            particle.c += particle.velocity.0;
+            particle.l += particle.velocity.1;
+            if particle.floats {
+                particle.l += rows as f32;
+            }
+            particle.age += 1;
+            particle.age < particle.lifetime
+        });
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Particle> {
+        self.particles.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+} // end of Particles implementation.
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+
+    use super::*;
+
+    #[test]
+    fn particles_expire() {
+        let mut particles = Particles::default();
+        let theme = Theme::default();
+        particles.spawn(
+            Effect::EnemyExplosion,
+            &Location::new(10, 10),
+            &theme,
+            &mut thread_rng(),
+        );
+        assert_eq!(particles.iter().count(), 8);
+
+        for _ in 0..9 {
+            particles.update(1);
+        }
+        assert!(particles.is_empty());
+    }
+
+    #[test]
+    fn debris_floats_with_the_river() {
+        let mut particles = Particles::default();
+        let theme = Theme::default();
+        let at = Location::new(10, 10);
+        particles.spawn(Effect::FuelExplosion, &at, &theme, &mut thread_rng());
+        particles.spawn(Effect::PlayerCrash, &at, &theme, &mut thread_rng());
+        // where each particle would be on still water
+        let still: Vec<f32> = particles
+            .iter()
+            .map(|particle| particle.l + particle.velocity.1 * 2.0)
+            .collect();
+
+        particles.update(2);
+        particles.update(0);
+        for (particle, still) in particles.iter().zip(still) {
+            let drift = if particle.floats { 2.0 } else { 0.0 };
+            assert!((particle.l - still - drift).abs() < 1e-4);
+        }
+    }
+}
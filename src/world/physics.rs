@@ -1,17 +1,48 @@
-use crate::World;
+use crate::{
+    world::{
+        fuel::REFUEL_RATE,
+        store::{Collider, Pickup},
+        Effect,
+    },
+    World,
+};
 
 use rand::Rng;
 use std::num::Wrapping;
 
-use crate::entities::{DeathCause, Enemy, EntityStatus, Fuel, PlayerStatus};
+use crate::{
+    drawable::Drawable,
+    entities::{DeathCause, EntityStatus, Location, PlayerStatus},
+    sound::SoundEvent,
+};
 
 impl World {
+    /// check if a cell is on the water
+    fn in_river(&self, (c, l): (i32, i32)) -> bool {
+        l >= 0
+            && self
+                .map
+                .get(l as usize)
+                .is_some_and(|&(left, right)| c >= left as i32 && c < right as i32)
+    }
+
     /// check if player hit the ground
     fn check_player_status(&mut self) {
-        if self.player.location.c < self.map[self.player.location.l as usize].0
-            || self.player.location.c >= self.map[self.player.location.l as usize].1
+        let player = self.player.sprite(self.glyphs, &self.theme).unwrap();
+        if !player
+            .footprint(&self.player.location)
+            .all(|cell| self.in_river(cell))
         {
-            self.player.status = PlayerStatus::Dead(DeathCause::Ground);
+            if self.boosts.absorb() {
+                // bounced back to the middle of the river
+                if let Some(&(left, right)) = self.map.get(self.player.location.l as usize) {
+                    self.player.location.c = (left + right) / 2;
+                }
+                self.player.velocity.0 = 0.0;
+                self.sound.emit(SoundEvent::ShieldHit);
+            } else {
+                self.player.status = PlayerStatus::Dead(DeathCause::Ground);
+            }
         }
 
         if self.player.gas == 0 {
@@ -19,30 +50,67 @@ impl World {
         }
     }
 
-    /// check enemy hit something
-    fn check_enemy_status(&mut self) {
-        // Remove dead
-        self.enemies
-            .retain(|f| !matches!(f.status, EntityStatus::Dead));
+    /// Forget what is gone for good, the wrecks go next frame
+    fn reap(&mut self) {
+        self.entities.bury();
+        for status in self.entities.status.iter_mut() {
+            if *status == EntityStatus::DeadBody {
+                *status = EntityStatus::Dead;
+            }
+        }
+    }
 
-        for enemy in self.enemies.iter_mut().rev() {
-            match enemy.status {
-                EntityStatus::Alive if self.player.location.hit(&enemy.location) => {
-                    self.player.status = PlayerStatus::Dead(DeathCause::Enemy);
-                }
-                EntityStatus::DeadBody => {
-                    enemy.status = EntityStatus::Dead;
-                }
-                _ => {}
+    /// The plane meets what it flies over: it crashes into hostiles, refuels
+    /// over depots and takes pickups
+    fn contacts(&mut self) {
+        let player = self.player.sprite(self.glyphs, &self.theme).unwrap();
+        let mut room = self.player.max_gas - self.player.gas;
+        let mut refuel = 0;
+        for entity in self.touching(&player, &self.player.location) {
+            if self.entities.status[entity] != EntityStatus::Alive {
+                continue;
             }
 
-            for bullet in self.bullets.iter().rev() {
-                if bullet.location.hit_with_margin(&enemy.location, 1, 0, 1, 0) {
-                    enemy.status = EntityStatus::DeadBody;
-                    self.player.score += 10;
+            match &mut self.entities.collider[entity] {
+                Some(Collider::Hostile) => {
+                    if self.boosts.absorb() {
+                        // the shield takes the enemy down, for no points
+                        let at = &self.entities.position[entity];
+                        self.particles.spawn(
+                            Effect::EnemyExplosion,
+                            at,
+                            &self.theme,
+                            &mut self.rng,
+                        );
+                        self.sound.emit(SoundEvent::ShieldHit);
+                        self.entities.status[entity] = EntityStatus::DeadBody;
+                    } else {
+                        self.player.status = PlayerStatus::Dead(DeathCause::Enemy);
+                    }
+                }
+                Some(Collider::Depot { gas }) => {
+                    // a bit of the depot every frame spent over it
+                    let amount = REFUEL_RATE.min(*gas).min(room);
+                    *gas -= amount;
+                    room -= amount;
+                    refuel += amount;
                 }
+                &mut Some(Collider::Pickup(pickup)) => {
+                    self.entities.status[entity] = EntityStatus::Dead;
+                    match pickup {
+                        Pickup::Weapon(weapon) => self.arm(weapon),
+                        Pickup::Boost(boost) => self.boost(boost),
+                    }
+                }
+                Some(Collider::Projectile { .. }) | None => {}
             }
         }
+
+        if refuel > 0 && !self.refueling {
+            self.sound.emit(SoundEvent::FuelPickedUp);
+        }
+        self.refueling = refuel > 0;
+        self.refuel(refuel);
     }
 
     /// Update the map
@@ -51,6 +119,11 @@ impl World {
 
         // move the map downward using VecDeque
         self.map.pop_back();
+        if let Some((left, right)) = self.scripted_banks() {
+            (self.next_left, self.next_right) = (left, right);
+            self.map.push_front((left, right));
+            return;
+        }
         let (mut left, mut right) = self.map[0];
         match self.next_left.cmp(&left) {
             Greater => left += 1,
@@ -74,130 +147,178 @@ impl World {
         }
 
         if self.next_right == self.map[0].1 && self.rng.gen_range(0..10) >= 7 {
-            self.next_right = self.rng.gen_range(self.next_right - 5..self.next_right + 5);
+            self.next_right = self
+                .rng
+                .gen_range(self.next_right.saturating_sub(5)..self.next_right + 5);
             if self.next_right > self.maxc {
                 self.next_right = Wrapping(self.maxc).0 - 1;
             }
         }
 
-        if self.next_right.abs_diff(self.next_left) < 3 {
-            self.next_right += 3;
+        // the banks must never cross, or there is no river to spawn in
+        if self.next_right < self.next_left + 3 {
+            self.next_right = (self.next_left + 3).min(self.maxc - 1);
+            self.next_left = self.next_right - 3;
         }
 
         self.map.push_front((left, right))
     }
 
-    /// Move enemies on the river
-    fn move_enemies(&mut self) {
-        self.enemies.retain_mut(|enemy| {
-            enemy.location.l += 1;
-            // Retain enemies within the screen
-            enemy.location.l < self.maxl
-        });
-    }
-
-    /// Move Bullets
-    fn move_bullets(&mut self) {
-        for index in (0..self.bullets.len()).rev() {
-            if self.bullets[index].energy == 0 || self.bullets[index].location.l <= 2 {
-                self.bullets.remove(index);
-            } else {
-                self.bullets[index].location.l -= 2;
-                self.bullets[index].energy -= 1;
-
-                if self.bullets[index].location.c
-                    < self.map[self.bullets[index].location.l as usize].0
-                    || self.bullets[index].location.c
-                        >= self.map[self.bullets[index].location.l as usize].1
-                {
-                    self.bullets.remove(index);
-                }
+    /// Carry what has no velocity of its own along with the river
+    fn float(&mut self) {
+        for entity in 0..self.entities.len() {
+            if self.entities.velocity[entity].is_none() {
+                let at = &self.entities.position[entity];
+                self.entities.move_to(entity, Location::new(at.c, at.l + 1));
             }
         }
     }
 
-    /// check if fuel is hit / moved over
-    fn check_fuel_status(&mut self) {
-        // Remove dead
-        self.fuels
-            .retain(|f| !matches!(f.status, EntityStatus::Dead));
-
-        for fuel in self.fuels.iter_mut().rev() {
-            match fuel.status {
-                EntityStatus::Alive if self.player.location.hit(&fuel.location) => {
-                    fuel.status = EntityStatus::DeadBody;
-                    self.player.gas += 200;
-                }
-                EntityStatus::DeadBody => {
-                    fuel.status = EntityStatus::Dead;
-                }
-                _ => {}
-            }
-
-            for bullet in self.bullets.iter().rev() {
-                if bullet.location.hit_with_margin(&fuel.location, 1, 0, 1, 0) {
-                    fuel.status = EntityStatus::DeadBody;
-                    self.player.score += 20;
-                }
+    /// What floated past the screen is gone, once the plane swept it
+    fn leave_screen(&mut self) {
+        for entity in 0..self.entities.len() {
+            if self.entities.position[entity].l >= self.maxl {
+                self.entities.status[entity] = EntityStatus::Dead;
             }
         }
     }
 
-    /// Create a new fuel; maybe
-    fn create_fuel(&mut self) {
-        // Possibility
-        if self.rng.gen_range(0..100) >= 99 {
-            self.fuels.push(Fuel::new(
-                self.rng.gen_range(self.map[0].0..self.map[0].1),
-                0,
-                EntityStatus::Alive,
-            ));
+    /// Count the lifetimes down, what runs out of it is gone
+    fn age(&mut self) {
+        let entities = &mut self.entities;
+        for (status, lifetime) in entities.status.iter_mut().zip(entities.lifetime.iter_mut()) {
+            match lifetime {
+                Some(0) => *status = EntityStatus::Dead,
+                Some(frames) => *frames -= 1,
+                None => {}
+            }
         }
     }
 
-    /// Create a new enemy
-    fn create_enemy(&mut self) {
-        // Possibility
-        if self.rng.gen_range(0..10) >= 9 {
-            self.enemies.push(Enemy::new(
-                self.rng.gen_range(self.map[0].0..self.map[0].1),
-                0,
-                EntityStatus::Alive,
-            ));
+    /// Move what has a velocity, it is gone once out of the river
+    fn fly(&mut self) {
+        for entity in 0..self.entities.len() {
+            let Some(velocity) = self.entities.velocity[entity] else {
+                continue;
+            };
+            if self.entities.status[entity] != EntityStatus::Alive {
+                continue;
+            }
+
+            let at = &self.entities.position[entity];
+            let c = at.c as i32 + velocity.c as i32;
+            let l = at.l as i32 + velocity.l as i32;
+            if c < 0 || l < 0 {
+                self.entities.status[entity] = EntityStatus::Dead;
+                continue;
+            }
+            self.entities
+                .move_to(entity, Location::new(c as u16, l as u16));
+
+            let in_river = {
+                let flying = self.entities.get(entity);
+                flying
+                    .sprite(self.glyphs, &self.theme)
+                    .is_some_and(|sprite| {
+                        sprite
+                            .footprint(flying.location())
+                            .all(|cell| self.in_river(cell))
+                    })
+            };
+            if !in_river {
+                self.entities.status[entity] = EntityStatus::Dead;
+            }
         }
     }
 
-    /// Move fuels on the river
-    fn move_fuel(&mut self) {
-        self.fuels.retain_mut(|fuel| {
-            fuel.location.l += 1;
-            // Retain fuels within the screen
-            fuel.location.l < self.maxl
-        });
-    }
+    /// Advance the world by one frame. The systems run in this order, each
+    /// one working on what the ones before left:
+    ///
+    /// 1. the plane: boosts, throttle, steering, the gun cooling down;
+    /// 2. the plane hitting the banks or running dry;
+    /// 3. the dead entities leave, the index follows;
+    /// 4. the plane meeting what it flies over;
+    /// 5. the river scrolling by, with new entities on each new row, the
+    ///    debris carried along;
+    /// 6. projectiles shooting down what they fly through;
+    /// 7. the plane running into what scrolled through it;
+    /// 8. what is past the screen leaving;
+    /// 9. lifetimes running out, and what flies on its own moving.
+    pub(crate) fn physics(&mut self) {
+        self.tick_boosts();
+        self.throttle();
+        self.move_player();
+        self.cool_down();
 
-    pub(super) fn physics(&mut self) {
         // check if player hit the ground
         self.check_player_status();
 
-        // check enemy hit something
-        self.check_enemy_status();
-        self.check_fuel_status();
+        // check the plane hit something
+        self.reap();
+        self.contacts();
 
+        // the river flows by at the speed of the plane
+        let rows = self.rows_to_scroll();
+        for _ in 0..rows {
+            self.scroll();
+        }
+        // the debris too, before more of it comes where the river is now
+        self.particles.update(rows);
+        // the bullets fly ahead of the plane
+        self.shoot_down(rows);
+        self.sweep_enemies(rows);
+        self.leave_screen();
+
+        if self.player.status != PlayerStatus::Alive {
+            let theme = &self.theme;
+            self.particles.spawn(
+                Effect::PlayerCrash,
+                &self.player.location,
+                theme,
+                &mut self.rng,
+            );
+            self.sound.emit(SoundEvent::Crash);
+        }
+        self.age();
+        self.fly();
+
+        // the index kept up to date is the one built from scratch
+        #[cfg(test)]
+        self.entities.check_index();
+    }
+
+    /// Fly one row further up the river
+    pub(super) fn scroll(&mut self) {
         // move the map Downward
         self.update_map();
 
-        // create new enemy
-        self.create_enemy();
-        self.create_fuel();
+        // Move elements along map movements, then fill the new row
+        self.float();
+        if self.script.is_some() {
+            self.play_script();
+        } else {
+            self.populate();
+        }
+
+        self.distance += 1;
+
+        self.burn_fuel();
+    }
+}
 
-        // Move elements along map movements
-        self.move_enemies();
-        self.move_fuel();
-        self.move_bullets();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        if self.player.gas >= 1 {
-            self.player.gas -= 1;
+    #[test]
+    fn the_banks_never_cross() {
+        let mut world = World::new(40, 20);
+        for _ in 0..5000 {
+            world.update_map();
+            let (left, right) = world.map[0];
+            assert!(left < right, "the banks crossed at {left} and {right}");
+            assert!(world.next_left + 3 <= world.next_right);
+            assert!(world.next_right <= world.maxc);
         }
     }
 }
@@ -0,0 +1,213 @@
+//! Power-ups floating down the river and the boosts they give for a while.
+//!
+//! Timers count frames of physics, not scrolled rows, so slow time does not
+//! make itself last longer.
+
+use crate::{canvas::Canvas, sound::SoundEvent, world::fuel::gauge, World};
+
+/// The boosts, in the order of the HUD
+const BOOSTS: [Boost; 3] = [Boost::Shield, Boost::Multiplier, Boost::SlowTime];
+
+/// Cells of a timer in the HUD
+const TIMER_WIDTH: usize = 4;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Boost {
+    /// Takes one crash, into an enemy or a bank, for the plane
+    Shield,
+    /// Doubles the points
+    Multiplier,
+    /// The river scrolls by at half the speed; the plane, the bullets and
+    /// the timers keep their pace
+    SlowTime,
+}
+
+impl Boost {
+    /// Frames the boost lasts
+    pub fn duration(self) -> u16 {
+        match self {
+            Boost::Shield => 500,
+            Boost::Multiplier => 300,
+            Boost::SlowTime => 150,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Boost::Shield => "shield",
+            Boost::Multiplier => "multiplier",
+            Boost::SlowTime => "slow_time",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Boost> {
+        BOOSTS.into_iter().find(|boost| boost.name() == name)
+    }
+
+    /// Short name for the HUD
+    fn label(self) -> &'static str {
+        match self {
+            Boost::Shield => "Shield",
+            Boost::Multiplier => "x2",
+            Boost::SlowTime => "Slow",
+        }
+    }
+}
+
+/// Frames left of each boost, 0 when it is off.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Boosts {
+    pub shield: u16,
+    pub multiplier: u16,
+    pub slow_time: u16,
+}
+
+impl Boosts {
+    /// Points are worth this many times more with the multiplier
+    pub const MULTIPLIER: u16 = 2;
+
+    /// Frames left of `boost`
+    pub fn left(&self, boost: Boost) -> u16 {
+        match boost {
+            Boost::Shield => self.shield,
+            Boost::Multiplier => self.multiplier,
+            Boost::SlowTime => self.slow_time,
+        }
+    }
+
+    fn timer(&mut self, boost: Boost) -> &mut u16 {
+        match boost {
+            Boost::Shield => &mut self.shield,
+            Boost::Multiplier => &mut self.multiplier,
+            Boost::SlowTime => &mut self.slow_time,
+        }
+    }
+
+    /// Points are worth this many times more
+    pub fn score_multiplier(&self) -> u16 {
+        if self.multiplier > 0 {
+            Boosts::MULTIPLIER
+        } else {
+            1
+        }
+    }
+
+    /// How fast the river scrolls by, 1 is normal
+    pub fn scroll_scale(&self) -> f32 {
+        if self.slow_time > 0 {
+            0.5
+        } else {
+            1.0
+        }
+    }
+
+    /// Use the shield up, if there is one
+    pub fn absorb(&mut self) -> bool {
+        let shielded = self.shield > 0;
+        self.shield = 0;
+        shielded
+    }
+}
+
+impl World {
+    /// Start the boost of a power-up, or start it over
+    pub(super) fn boost(&mut self, boost: Boost) {
+        *self.boosts.timer(boost) = boost.duration();
+        self.sound.emit(SoundEvent::PowerUpPicked);
+    }
+
+    /// Count the boosts down, one frame
+    pub(super) fn tick_boosts(&mut self) {
+        for boost in BOOSTS {
+            let timer = self.boosts.timer(boost);
+            *timer = timer.saturating_sub(1);
+        }
+    }
+
+    /// The running boosts with the time they have left
+    pub(super) fn draw_boost_timers(&self, canvas: &mut Canvas, (mut c, l): (u16, u16)) {
+        let style = canvas.theme().hud_style();
+        for boost in BOOSTS {
+            let left = self.boosts.left(boost);
+            if left == 0 {
+                continue;
+            }
+
+            let fraction = left as f32 / boost.duration() as f32;
+            let timer = format!(
+                " {} {} ",
+                boost.label(),
+                gauge(fraction, TIMER_WIDTH, canvas.glyphs().gauge)
+            );
+            let width = timer.chars().count() as u16;
+            canvas.draw_styled_line((c, l), timer, style);
+            c += width + 1;
+        }
+    }
+} // end of World implementation.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        content::Content,
+        entities::{Location, PlayerStatus},
+        world::Weapon,
+    };
+
+    #[test]
+    fn the_shield_takes_one_crash() {
+        let mut world = World::new(60, 30);
+        world.boosts.shield = Boost::Shield.duration();
+        let (c, l) = (world.player.location.c, world.player.location.l);
+        world.entities.spawn(
+            Content::built_in().kind("enemy").unwrap(),
+            Location::new(c, l),
+        );
+        world.physics();
+        assert_eq!(world.player.status, PlayerStatus::Alive);
+        assert_eq!(world.boosts.shield, 0);
+
+        // into the bank, with no shield left
+        world.player.location.c = 2;
+        world.physics();
+        assert!(matches!(world.player.status, PlayerStatus::Dead(_)));
+    }
+
+    #[test]
+    fn the_multiplier_doubles_the_points() {
+        let mut world = World::new(60, 30);
+        world.boosts.multiplier = Boost::Multiplier.duration();
+        let (c, l) = (world.player.location.c, world.player.location.l);
+        world.entities.spawn(
+            Content::built_in().kind("enemy").unwrap(),
+            Location::new(c, l - 4),
+        );
+        let bullet = world.bullet(Weapon::Single, 0);
+        world.entities.spawn(&bullet, Location::new(c, l - 3));
+        world.physics();
+        assert_eq!(world.player.score, 20);
+    }
+
+    #[test]
+    fn slow_time_runs_out() {
+        let mut world = World::new(60, 30);
+        let at = world.player.location.clone();
+        world
+            .entities
+            .spawn(Content::built_in().kind("slow_time").unwrap(), at);
+        for _ in 0..10 {
+            world.physics();
+        }
+        assert_eq!(world.distance, 5);
+
+        let mut canvas = Canvas::new(60, 30);
+        world.draw_on_canvas(&mut canvas);
+        assert!(canvas.text().contains(" Slow "));
+
+        for _ in 0..Boost::SlowTime.duration() {
+            world.tick_boosts();
+        }
+        assert_eq!(world.boosts.scroll_scale(), 1.0);
+    }
+}
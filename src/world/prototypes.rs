@@ -0,0 +1,82 @@
+//! The spawners putting what floats down the river on each new row.
+//!
+//! Their prototypes come from the content pack: a new enemy or pickup is a
+//! kind with the components it needs and a line in a spawn table, the
+//! systems of `physics` do the rest.
+
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+
+use crate::{entities::Location, world::store::Prototype, World};
+
+/// Puts one of its prototypes on a new row, now and then.
+#[derive(Clone, Debug)]
+pub struct Spawner {
+    /// Chance on each new row, one in
+    pub odds: u32,
+    /// Columns kept off the banks, when the river is wide enough
+    pub margin: u16,
+    /// The prototypes, with how often each shows up relative to the others
+    pub table: Vec<(Prototype, u32)>,
+}
+
+impl World {
+    /// Let each spawner try its luck on the new top row
+    pub(super) fn populate(&mut self) {
+        let (left, right) = self.map[0];
+        for spawner in self.spawners.iter() {
+            if self.rng.gen_range(0..spawner.odds) != 0 {
+                continue;
+            }
+
+            let weights = WeightedIndex::new(spawner.table.iter().map(|(_, weight)| weight));
+            let Ok(weights) = weights else {
+                continue;
+            };
+            let prototype = &spawner.table[weights.sample(&mut self.rng)].0;
+            let columns = if right - left > 2 * spawner.margin {
+                left + spawner.margin..right - spawner.margin
+            } else {
+                left.min(right - 1)..right
+            };
+            let column = self.rng.gen_range(columns);
+            self.entities.spawn(prototype, Location::new(column, 0));
+        }
+    }
+} // end of World implementation.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::Content;
+
+    #[test]
+    fn spawners_keep_to_the_river() {
+        let content = Content::built_in();
+        let (depot, enemy) = (
+            content.kind("depot").unwrap(),
+            content.kind("enemy").unwrap(),
+        );
+        let mut world = World::new(40, 20);
+        world.spawners = vec![Spawner {
+            odds: 1,
+            margin: 1,
+            table: vec![(depot.clone(), 1), (enemy.clone(), 0)],
+        }];
+        world.map[0] = (10, 14);
+        for _ in 0..50 {
+            world.populate();
+        }
+
+        assert_eq!(world.entities.len(), 50);
+        assert!(world
+            .entities
+            .health
+            .iter()
+            .all(|&health| health == Some(1)));
+        assert!(world
+            .entities
+            .position
+            .iter()
+            .all(|at| (11..13).contains(&at.c)));
+    }
+}
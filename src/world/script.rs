@@ -0,0 +1,142 @@
+//! Playing a level file: the banks and what is on the river come from the
+//! level, row after row, instead of the random walk and the spawners.
+
+use crate::{
+    content::Content,
+    entities::Location,
+    level::{Level, LevelEnd},
+    world::store::Prototype,
+    World,
+};
+
+/// A level being played.
+pub struct Script {
+    level: Level,
+    /// The prototypes of the places of the level, in the same order
+    kinds: Vec<Prototype>,
+    /// Row of the level coming up next
+    row: u32,
+}
+
+impl Script {
+    pub fn new(level: &Level, content: &Content, from: u32) -> Script {
+        let kinds = level
+            .places
+            .iter()
+            .map(|place| {
+                content
+                    .kind(&place.kind)
+                    .expect("levels are checked against the pack")
+                    .clone()
+            })
+            .collect();
+        Script {
+            level: level.clone(),
+            kinds,
+            row: from,
+        }
+    }
+}
+
+impl World {
+    /// The banks of the new row, while a level is played
+    pub(super) fn scripted_banks(&self) -> Option<(u16, u16)> {
+        let script = self.script.as_ref()?;
+        Some(script.level.banks(script.row, self.maxc))
+    }
+
+    /// Put what the level places on the new top row, then go to the next row
+    pub(super) fn play_script(&mut self) {
+        let Some(script) = &mut self.script else {
+            return;
+        };
+        let places = script.level.places.iter().zip(script.kinds.iter());
+        for (place, prototype) in places.filter(|(place, _)| place.at == script.row) {
+            for column in script.level.columns(place, self.maxc) {
+                self.entities.spawn(prototype, Location::new(column, 0));
+            }
+        }
+
+        script.row += 1;
+        if script.row >= script.level.length() {
+            match script.level.end {
+                LevelEnd::Loop => script.row = 0,
+                // the random walk goes on from where the level left the banks
+                LevelEnd::Procedural => self.script = None,
+            }
+        }
+    }
+} // end of World implementation.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::store::Collider;
+
+    fn level(end: &str) -> Level {
+        let text = format!(
+            r#"
+            end = "{end}"
+
+            [[section]]
+            length = 4
+            width = 10
+
+            [[section]]
+            length = 2
+            width = 6
+            offset = 10
+
+            [[place]]
+            at = 1
+            kind = "enemy"
+            column = -20
+
+            [[place]]
+            at = 5
+            kind = "bridge"
+            span = true
+            "#
+        );
+        Level::from_toml(&text, Content::built_in()).unwrap()
+    }
+
+    fn hostiles(world: &World) -> Vec<u16> {
+        world
+            .entities
+            .alive(|collider| *collider == Collider::Hostile)
+            .map(|entity| world.entities.position[entity].c)
+            .collect()
+    }
+
+    #[test]
+    fn the_river_follows_the_level() {
+        let content = Content::built_in();
+        let mut world = World::new(40, 20).with_level(&level("procedural"), content, 0);
+        world.spawners.clear();
+        assert_eq!(world.map[19], (15, 25));
+        assert_eq!(world.player.location.c, 20);
+
+        for _ in 0..6 {
+            world.scroll();
+        }
+        assert_eq!(world.map[5], (15, 25));
+        assert_eq!(world.map[0], (27, 33));
+        // the enemy is kept in the river, the bridge goes from bank to bank
+        assert_eq!(hostiles(&world), [15, 27, 28, 29, 30, 31, 32]);
+        assert!(world.script.is_none());
+    }
+
+    #[test]
+    fn looping_levels_start_over() {
+        let content = Content::built_in();
+        let mut world = World::new(40, 20).with_level(&level("loop"), content, 0);
+        world.spawners.clear();
+        for _ in 0..8 {
+            world.scroll();
+        }
+        assert!(world.script.is_some());
+        assert_eq!(world.map[0], (15, 25));
+        assert_eq!(hostiles(&world).len(), 8);
+    }
+}
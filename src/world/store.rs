@@ -0,0 +1,307 @@
+//! A small entity-component store for everything on the river but the plane.
+//!
+//! An entity is an index into the columns of components, it has the
+//! components it needs and `None` for the others. What an entity does comes
+//! from its components and not from a type: whatever has health can be shot,
+//! whatever has no velocity floats down with the river. The systems working
+//! on them are `World` methods, run in order by `physics`.
+//!
+//! The store also keeps the [`SpatialIndex`] of where the entities are, so
+//! they are moved with [`Store::move_to`] and not through `position`.
+
+use crate::{
+    entities::{EntityStatus, Location},
+    glyphs::{Glyph, GlyphSet, ASCII, UNICODE},
+    sprite::{Rect, SpriteArt},
+    theme::Paint,
+    world::{index::SpatialIndex, Boost, Weapon},
+};
+
+pub type Entity = usize;
+
+/// A sprite of the glyph set, or one of a content pack.
+#[derive(Clone, Copy, Debug)]
+pub enum Art {
+    Glyph(Glyph),
+    Custom(&'static CustomArt),
+}
+
+/// Art of a content pack, with a version for the ASCII glyph set.
+#[derive(Debug)]
+pub struct CustomArt {
+    pub unicode: SpriteArt,
+    pub ascii: SpriteArt,
+}
+
+impl Art {
+    pub fn sprite_art(self, glyphs: &GlyphSet) -> &SpriteArt {
+        match self {
+            Art::Glyph(glyph) => glyphs.art(glyph),
+            Art::Custom(art) if glyphs.name == ASCII.name => &art.ascii,
+            Art::Custom(art) => &art.unicode,
+        }
+    }
+}
+
+/// How an entity is drawn.
+#[derive(Clone, Copy, Debug)]
+pub struct Look {
+    pub art: Art,
+    /// Shown the frame it is destroyed, nothing when `None`
+    pub wreck: Option<Art>,
+    pub paint: Paint,
+    /// What is drawn over what, higher on top
+    pub layer: u8,
+    /// The first cell fades out, like the tail of a bullet
+    pub trail: bool,
+}
+
+impl Look {
+    pub fn new(art: Art, paint: Paint) -> Look {
+        Look {
+            art,
+            wreck: None,
+            paint,
+            layer: 0,
+            trail: false,
+        }
+    }
+}
+
+/// Cells a frame on the screen.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Velocity {
+    pub c: i16,
+    pub l: i16,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Pickup {
+    Weapon(Weapon),
+    Boost(Boost),
+}
+
+/// What happens when something runs into the entity.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Collider {
+    /// Crashes the plane
+    Hostile,
+    /// Fills the tank of the plane over it, with the gas left
+    Depot { gas: u16 },
+    /// Taken by the plane flying over it
+    Pickup(Pickup),
+    /// Spent on the first thing with health it hits
+    Projectile { damage: u16 },
+}
+
+/// The components of an entity to spawn, all but the position.
+#[derive(Clone, Debug)]
+pub struct Prototype {
+    pub sprite: Look,
+    pub velocity: Option<Velocity>,
+    pub collider: Option<Collider>,
+    pub health: Option<u16>,
+    pub lifetime: Option<u16>,
+    pub score: Option<u16>,
+}
+
+impl Prototype {
+    /// A bare entity with only a look, floating with the river
+    pub fn new(sprite: Look) -> Prototype {
+        Prototype {
+            sprite,
+            velocity: None,
+            collider: None,
+            health: None,
+            lifetime: None,
+            score: None,
+        }
+    }
+}
+
+/// The columns of components, one row per entity.
+pub struct Store {
+    pub status: Vec<EntityStatus>,
+    pub position: Vec<Location>,
+    pub sprite: Vec<Look>,
+    pub velocity: Vec<Option<Velocity>>,
+    pub collider: Vec<Option<Collider>>,
+    pub health: Vec<Option<u16>>,
+    /// Frames left to live
+    pub lifetime: Vec<Option<u16>>,
+    /// Points for shooting it down
+    pub score: Vec<Option<u16>>,
+    /// Where the entities are, by the cells of their sprites
+    pub(super) index: SpatialIndex,
+    /// The sprites the entities are indexed with
+    glyphs: &'static GlyphSet,
+}
+
+impl Default for Store {
+    fn default() -> Self {
+        Store::new(&UNICODE)
+    }
+}
+
+impl Store {
+    /// An empty store, indexing the entities by their sprites of `glyphs`
+    pub fn new(glyphs: &'static GlyphSet) -> Store {
+        Store {
+            status: vec![],
+            position: vec![],
+            sprite: vec![],
+            velocity: vec![],
+            collider: vec![],
+            health: vec![],
+            lifetime: vec![],
+            score: vec![],
+            index: SpatialIndex::default(),
+            glyphs,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.status.len()
+    }
+
+    /// The cells of the entity anchored at `at`, the way it is indexed
+    pub(super) fn area(&self, entity: Entity, at: &Location) -> Rect {
+        self.sprite[entity].art.sprite_art(self.glyphs).bounds(at)
+    }
+
+    pub fn spawn(&mut self, prototype: &Prototype, at: Location) -> Entity {
+        self.status.push(EntityStatus::Alive);
+        self.position.push(at);
+        self.sprite.push(prototype.sprite);
+        self.velocity.push(prototype.velocity);
+        self.collider.push(prototype.collider);
+        self.health.push(prototype.health);
+        self.lifetime.push(prototype.lifetime);
+        self.score.push(prototype.score);
+
+        let entity = self.len() - 1;
+        self.index
+            .insert(entity, self.area(entity, &self.position[entity]));
+        entity
+    }
+
+    /// Move an entity, in the index too.
+    pub fn move_to(&mut self, entity: Entity, to: Location) {
+        let from = self.area(entity, &self.position[entity]);
+        let area = self.area(entity, &to);
+        self.position[entity] = to;
+        if area != from {
+            self.index.remove(entity, from);
+            self.index.insert(entity, area);
+        }
+    }
+
+    /// The living entities with a collider matching `is`.
+    pub fn alive<'a>(
+        &'a self,
+        is: impl Fn(&Collider) -> bool + 'a,
+    ) -> impl Iterator<Item = Entity> + 'a {
+        (0..self.len()).filter(move |&entity| {
+            self.status[entity] == EntityStatus::Alive
+                && self.collider[entity].as_ref().is_some_and(&is)
+        })
+    }
+
+    /// Drop the dead entities; the others are renumbered.
+    pub fn bury(&mut self) {
+        let alive: Vec<bool> = self
+            .status
+            .iter()
+            .map(|&status| status != EntityStatus::Dead)
+            .collect();
+
+        // the index forgets the dead, and renumbers the ones after them
+        let mut number = 0;
+        for (entity, &alive) in alive.iter().enumerate() {
+            if alive {
+                if number != entity {
+                    let area = self.area(entity, &self.position[entity]);
+                    self.index.renumber(entity, number, area);
+                }
+                number += 1;
+            } else {
+                let area = self.area(entity, &self.position[entity]);
+                self.index.remove(entity, area);
+            }
+        }
+
+        fn keep<T>(column: &mut Vec<T>, alive: &[bool]) {
+            let mut alive = alive.iter();
+            column.retain(|_| *alive.next().unwrap());
+        }
+        keep(&mut self.status, &alive);
+        keep(&mut self.position, &alive);
+        keep(&mut self.sprite, &alive);
+        keep(&mut self.velocity, &alive);
+        keep(&mut self.collider, &alive);
+        keep(&mut self.health, &alive);
+        keep(&mut self.lifetime, &alive);
+        keep(&mut self.score, &alive);
+    }
+
+    pub fn get(&self, entity: Entity) -> EntityRef<'_> {
+        EntityRef {
+            store: self,
+            entity,
+        }
+    }
+} // end of Store implementation.
+
+/// One entity of the store, to draw it.
+pub struct EntityRef<'a> {
+    pub store: &'a Store,
+    pub entity: Entity,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn burying_keeps_the_components_together() {
+        let mut store = Store::default();
+        let enemy = Prototype {
+            collider: Some(Collider::Hostile),
+            health: Some(2),
+            ..Prototype::new(Look::new(Art::Glyph(Glyph::Enemy), Paint::Enemy))
+        };
+        let depot = Prototype::new(Look::new(Art::Glyph(Glyph::Fuel), Paint::Fuel));
+        store.spawn(&enemy, Location::new(1, 1));
+        store.spawn(&depot, Location::new(2, 2));
+        store.spawn(&enemy, Location::new(3, 3));
+
+        store.status[0] = EntityStatus::Dead;
+        store.bury();
+        store.check_index();
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.health, [None, Some(2)]);
+        assert_eq!(store.position[1].c, 3);
+        assert_eq!(
+            store
+                .alive(|collider| *collider == Collider::Hostile)
+                .collect::<Vec<_>>(),
+            [1]
+        );
+    }
+
+    #[test]
+    fn moving_keeps_the_index() {
+        let mut store = Store::default();
+        let depot = Prototype::new(Look::new(Art::Glyph(Glyph::Fuel), Paint::Fuel));
+        let enemy = Prototype::new(Look::new(Art::Glyph(Glyph::Enemy), Paint::Enemy));
+        store.spawn(&depot, Location::new(4, 0));
+        store.spawn(&enemy, Location::new(6, 2));
+        store.check_index();
+
+        store.move_to(0, Location::new(4, 3));
+        store.move_to(1, Location::new(7, 2));
+        store.check_index();
+        assert_eq!(store.index.overlapping(Rect::new(0, 0, 10, 3)).count(), 1);
+        assert_eq!(store.index.overlapping(Rect::new(0, 3, 10, 3)).count(), 1);
+    }
+}
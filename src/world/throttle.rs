@@ -0,0 +1,112 @@
+//! The arcade controls: up and down push and pull the throttle.
+//!
+//! The river scrolls by `speed` rows a frame, everything tied to the ground
+//! (the banks, enemies, fuel, the distance and the fuel burnt) follows the
+//! rows actually scrolled and not the frames.
+
+use crate::World;
+
+/// Rows a frame with the stick centered
+pub const CRUISE: f32 = 1.0;
+const SLOWEST: f32 = 0.5;
+const FASTEST: f32 = 2.0;
+/// How much the speed changes in a frame
+const THROTTLE_STEP: f32 = 0.1;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Controls {
+    /// Up and down move the plane on the screen
+    Free,
+    /// Up and down change the speed, like the original River Raid
+    Throttle,
+}
+
+impl Controls {
+    pub fn name(self) -> &'static str {
+        match self {
+            Controls::Free => "free",
+            Controls::Throttle => "throttle",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Controls> {
+        [Controls::Free, Controls::Throttle]
+            .into_iter()
+            .find(|controls| controls.name() == name)
+    }
+}
+
+impl World {
+    /// Bring the speed toward what the stick asks for.
+    pub(super) fn throttle(&mut self) {
+        if self.controls != Controls::Throttle {
+            return;
+        }
+
+        let target = match self.player.steering.1 {
+            -1 => FASTEST,
+            1 => SLOWEST,
+            _ => CRUISE,
+        };
+        self.speed = if self.speed < target {
+            (self.speed + THROTTLE_STEP).min(target)
+        } else {
+            (self.speed - THROTTLE_STEP).max(target)
+        };
+    }
+
+    /// How many rows to scroll this frame, slowed down by slow time.
+    pub(super) fn rows_to_scroll(&mut self) -> u16 {
+        self.scrolled += self.speed * self.boosts.scroll_scale();
+        let rows = self.scrolled.trunc();
+        self.scrolled -= rows;
+        rows as u16
+    }
+} // end of World implementation.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas::Canvas;
+
+    fn fly(world: &mut World, steering: i8, frames: usize) -> u16 {
+        world.player.steering.1 = steering;
+        (0..frames)
+            .map(|_| {
+                world.throttle();
+                world.rows_to_scroll()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn speed_follows_the_stick() {
+        let mut world = World::new(40, 20).with_controls(Controls::Throttle);
+        fly(&mut world, -1, 20);
+        assert_eq!(world.speed, FASTEST);
+        assert_eq!(fly(&mut world, -1, 10), 20);
+
+        fly(&mut world, 1, 20);
+        assert_eq!(world.speed, SLOWEST);
+        assert_eq!(fly(&mut world, 1, 10), 5);
+
+        fly(&mut world, 0, 20);
+        assert_eq!(world.speed, CRUISE);
+    }
+
+    #[test]
+    fn free_controls_keep_the_speed() {
+        let mut world = World::new(40, 20);
+        assert_eq!(fly(&mut world, -1, 10), 10);
+        assert_eq!(world.speed, CRUISE);
+    }
+
+    #[test]
+    fn hud_shows_the_speed() {
+        let mut world = World::new(40, 20).with_controls(Controls::Throttle);
+        fly(&mut world, -1, 3);
+        let mut canvas = Canvas::new(40, 20);
+        world.draw_on_canvas(&mut canvas);
+        assert!(canvas.text().contains("Speed: 1.3x"));
+    }
+}
@@ -0,0 +1,206 @@
+//! The guns of the plane and the ammo crates floating down the river.
+//!
+//! The plane always has its single shot. Crates swap it for a better weapon
+//! until their rounds are spent.
+
+use crate::{
+    entities::{Location, PlayerStatus},
+    sound::SoundEvent,
+    world::store::{Collider, Prototype, Velocity},
+    World,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Weapon {
+    Single,
+    Rapid,
+    Spread,
+}
+
+/// How a weapon shoots.
+pub struct WeaponStats {
+    /// Frames between two shots
+    pub cooldown: u16,
+    /// Lines a bullet flies each frame
+    pub speed: u16,
+    /// One bullet for each, with the columns it drifts each frame
+    pub spread: &'static [i16],
+    pub damage: u16,
+    /// Shots in a crate, the single shot never runs out
+    pub rounds: u16,
+}
+
+impl Weapon {
+    pub fn stats(self) -> WeaponStats {
+        match self {
+            Weapon::Single => WeaponStats {
+                cooldown: 6,
+                speed: 2,
+                spread: &[0],
+                damage: 2,
+                rounds: 0,
+            },
+            Weapon::Rapid => WeaponStats {
+                cooldown: 1,
+                speed: 2,
+                spread: &[0],
+                damage: 1,
+                rounds: 80,
+            },
+            // a short range shotgun
+            Weapon::Spread => WeaponStats {
+                cooldown: 5,
+                speed: 1,
+                spread: &[-1, 0, 1],
+                damage: 2,
+                rounds: 25,
+            },
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Weapon::Single => "single",
+            Weapon::Rapid => "rapid",
+            Weapon::Spread => "spread",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Weapon> {
+        [Weapon::Single, Weapon::Rapid, Weapon::Spread]
+            .into_iter()
+            .find(|weapon| weapon.name() == name)
+    }
+}
+
+impl World {
+    /// Shoot, if the gun is ready
+    pub fn fire(&mut self) {
+        if self.player.status != PlayerStatus::Alive || self.player.cooldown > 0 {
+            return;
+        }
+
+        let stats = self.player.weapon.stats();
+        let Location { c, l } = self.player.location;
+        for &drift in stats.spread {
+            let bullet = self.bullet(self.player.weapon, drift);
+            self.entities.spawn(&bullet, Location::new(c, l - 1));
+        }
+        self.player.cooldown = stats.cooldown;
+        self.sound.emit(SoundEvent::ShotFired);
+
+        if self.player.weapon != Weapon::Single {
+            self.player.ammo = self.player.ammo.saturating_sub(1);
+            if self.player.ammo == 0 {
+                self.player.weapon = Weapon::Single;
+            }
+        }
+    }
+
+    /// A shot of `weapon` drifting `drift` columns a frame
+    pub(super) fn bullet(&self, weapon: Weapon, drift: i16) -> Prototype {
+        let stats = weapon.stats();
+        Prototype {
+            velocity: Some(Velocity {
+                c: drift,
+                l: -(stats.speed as i16),
+            }),
+            collider: Some(Collider::Projectile {
+                damage: stats.damage,
+            }),
+            lifetime: Some(self.maxl / 4),
+            ..Prototype::new(self.bullet)
+        }
+    }
+
+    pub(super) fn cool_down(&mut self) {
+        self.player.cooldown = self.player.cooldown.saturating_sub(1);
+    }
+
+    /// Swap the gun for the one of a crate
+    pub(super) fn arm(&mut self, weapon: Weapon) {
+        self.player.weapon = weapon;
+        self.player.ammo = weapon.stats().rounds;
+        self.sound.emit(SoundEvent::WeaponPickedUp);
+    }
+} // end of World implementation.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{content::Content, entities::EntityStatus, world::store::Store};
+
+    fn bullets(entities: &Store) -> Vec<usize> {
+        (0..entities.len())
+            .filter(|&entity| {
+                matches!(entities.collider[entity], Some(Collider::Projectile { .. }))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn spread_shots_fan_out() {
+        let mut world = World::new(60, 30);
+        world.player.weapon = Weapon::Spread;
+        world.player.ammo = 1;
+        world.fire();
+        assert_eq!(bullets(&world.entities).len(), 3);
+        assert_eq!(world.player.weapon, Weapon::Single);
+
+        world.physics();
+        let columns: Vec<u16> = bullets(&world.entities)
+            .into_iter()
+            .map(|bullet| world.entities.position[bullet].c)
+            .collect();
+        assert_eq!(columns, [29, 30, 31]);
+    }
+
+    #[test]
+    fn the_gun_cools_down() {
+        let mut world = World::new(60, 30);
+        world.fire();
+        world.fire();
+        assert_eq!(bullets(&world.entities).len(), 1);
+
+        for _ in 0..Weapon::Single.stats().cooldown {
+            world.cool_down();
+        }
+        world.fire();
+        assert_eq!(bullets(&world.entities).len(), 2);
+    }
+
+    #[test]
+    fn rapid_fire_needs_two_hits() {
+        let mut world = World::new(60, 30);
+        world.player.weapon = Weapon::Rapid;
+        world.player.ammo = 10;
+        let Location { c, l } = world.player.location;
+        let enemy = world.entities.spawn(
+            Content::built_in().kind("enemy").unwrap(),
+            Location::new(c, l - 8),
+        );
+
+        let mut healths = Vec::new();
+        while world.entities.status[enemy] == EntityStatus::Alive {
+            world.fire();
+            world.physics();
+            healths.push(world.entities.health[enemy]);
+            assert!(healths.len() < 10, "the enemy was never shot down");
+        }
+        assert!(healths.contains(&Some(1)));
+        assert_eq!(world.player.score, 10);
+    }
+
+    #[test]
+    fn crates_change_the_weapon() {
+        let mut world = World::new(60, 30);
+        let Location { c, l } = world.player.location;
+        world.entities.spawn(
+            Content::built_in().kind("rapid").unwrap(),
+            Location::new(c, l),
+        );
+        world.physics();
+        assert_eq!(world.player.weapon, Weapon::Rapid);
+        assert_eq!(world.player.ammo, Weapon::Rapid.stats().rounds);
+    }
+}